@@ -0,0 +1,177 @@
+use crate::{HandlerInCtx, Result};
+
+/// An asynchronous handler to handle an APDU command and receive a response in a specific context.
+///
+/// The futures returned are not bound to be [`Send`], so that handlers on `!Sync` readers
+/// (and `Rc`s of them) can be implemented.
+/// As a consequence, generic code cannot spawn them onto multi-threaded executors;
+/// use a concrete handler type whose futures are `Send`, or a local executor instead.
+#[allow(async_fn_in_trait)]
+pub trait AsyncHandlerInCtx<Ctx = ()> {
+    /// Handles the APDU command in a specific context.
+    /// Implementations must transmit the command to the card through a reader,
+    /// then receive the response from them, returning length of the data written.
+    async fn handle_in_ctx(&self, ctx: Ctx, command: &[u8], response: &mut [u8]) -> Result;
+}
+
+/// An asynchronous handler to handle an APDU command and receive a response.
+/// Implemented for all [`AsyncHandlerInCtx<()>`]s.
+/// The futures are not bound to be [`Send`], as in [`AsyncHandlerInCtx`].
+#[allow(async_fn_in_trait)]
+pub trait AsyncHandler: AsyncHandlerInCtx<()> {
+    /// Handles the APDU command.
+    /// Implementations must transmit the command to the card through a reader,
+    /// then receive the response from them, returning length of the data written.
+    async fn handle(&self, command: &[u8], response: &mut [u8]) -> Result {
+        self.handle_in_ctx((), command, response).await
+    }
+}
+
+impl<T: AsyncHandlerInCtx<()> + ?Sized> AsyncHandler for T {}
+
+macro_rules! impl_async_handler_in_ctx_deref {
+    ($($ty: ty),*) => {
+        $(
+            impl<Ctx, T: AsyncHandlerInCtx<Ctx> + ?Sized> AsyncHandlerInCtx<Ctx> for $ty {
+                async fn handle_in_ctx(&self, ctx: Ctx, command: &[u8], response: &mut [u8]) -> Result {
+                    (**self).handle_in_ctx(ctx, command, response).await
+                }
+            }
+        )*
+    };
+}
+
+impl_async_handler_in_ctx_deref!(&T, &mut T);
+
+#[cfg(feature = "alloc")]
+impl_async_handler_in_ctx_deref!(alloc::boxed::Box<T>, alloc::rc::Rc<T>, alloc::sync::Arc<T>);
+
+/// An adapter to use a synchronous handler as an asynchronous one.
+/// The handler is called in place, blocking the task until it returns.
+#[derive(Clone, Copy, Debug)]
+pub struct SyncToAsync<H>(pub H);
+
+impl<Ctx, H: HandlerInCtx<Ctx>> AsyncHandlerInCtx<Ctx> for SyncToAsync<H> {
+    async fn handle_in_ctx(&self, ctx: Ctx, command: &[u8], response: &mut [u8]) -> Result {
+        self.0.handle_in_ctx(ctx, command, response)
+    }
+}
+
+/// An adapter to use an asynchronous handler as a synchronous one.
+/// The handler is driven on the current thread without any executor, blocking it until the
+/// response is received: the thread is parked while the future is pending, and the future is
+/// polled again each time its waker is woken.
+/// Handlers that need a runtime, such as ones using tokio's I/O or timers, cannot be driven
+/// by this adapter; use `block_on` of the runtime instead.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug)]
+pub struct AsyncToSync<H>(pub H);
+
+#[cfg(feature = "std")]
+impl<Ctx, H: AsyncHandlerInCtx<Ctx>> HandlerInCtx<Ctx> for AsyncToSync<H> {
+    fn handle_in_ctx(&self, ctx: Ctx, command: &[u8], response: &mut [u8]) -> Result {
+        block_on(self.0.handle_in_ctx(ctx, command, response))
+    }
+}
+
+/// Drives the future to completion on the current thread, blocking it.
+/// The thread is parked while the future is pending, and unparked by the waker to poll it again.
+#[cfg(feature = "std")]
+pub(crate) fn block_on<F: core::future::Future>(future: F) -> F::Output {
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+    use std::thread::Thread;
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let mut future = core::pin::pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(std::thread::current())));
+    let mut cx = Context::from_waker(&waker);
+
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => std::thread::park(),
+        }
+    }
+}
+
+/// An asynchronous transport to transmit an APDU command and receive a parsed response.
+/// Implemented for all [`AsyncHandler`]s, allocating the response buffer from the Ne of the command.
+/// The futures are not bound to be [`Send`], as in [`AsyncHandlerInCtx`].
+#[cfg(feature = "alloc")]
+#[allow(async_fn_in_trait)]
+pub trait AsyncTransport {
+    /// Transmits the command, returning the owned response with the trailer split off.
+    /// The command is never resent, so it is safe to transmit non-idempotent commands.
+    async fn transmit<'a>(
+        &self,
+        command: impl Into<crate::Command<'a>>,
+    ) -> core::result::Result<crate::ResponseBuf, crate::TransmitError>;
+}
+
+#[cfg(feature = "alloc")]
+impl<H: AsyncHandler + ?Sized> AsyncTransport for H {
+    async fn transmit<'a>(
+        &self,
+        command: impl Into<crate::Command<'a>>,
+    ) -> core::result::Result<crate::ResponseBuf, crate::TransmitError> {
+        let command = command.into();
+        let bytes = command.to_vec()?;
+
+        let mut buf = alloc::vec![0u8; command.expected_len()? + crate::transport::TRAILER_LEN];
+        let len = self.handle(&bytes, &mut buf).await?;
+
+        crate::transport::into_response(buf, len)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::mock::MockCard;
+    use crate::{handler_fn, Command, Handler, StatusWord};
+
+    #[test]
+    fn async_transmit() {
+        let handler = MockCard::new(|_| vec![0x01, 0x02, 0x90, 0x00]);
+        let response =
+            block_on(handler.transmit(Command::new_with_le(0x00, 0xB0, 0x00, 0x00, 0x02))).unwrap();
+
+        assert_eq!(vec![0x01, 0x02], response.payload);
+        assert_eq!(StatusWord::OK, response.trailer);
+        assert_eq!(
+            vec![vec![0x00, 0xB0, 0x00, 0x00, 0x02]],
+            *handler.commands.borrow(),
+        );
+    }
+
+    #[test]
+    fn adapters() {
+        let handler = AsyncToSync(MockCard::new(|_| vec![0x90, 0x00]));
+        let mut buf = [0u8; 2];
+
+        assert_eq!(
+            Ok(2),
+            handler
+                .handle(&[0x00, 0xA4, 0x00, 0x00], &mut buf)
+                .map_err(|_| ())
+        );
+        assert_eq!([0x90, 0x00], buf);
+        assert_eq!(1, handler.0.commands.borrow().len());
+
+        let handler = SyncToAsync(handler_fn(|_, response| {
+            response[..2].copy_from_slice(&[0x6A, 0x82]);
+            Ok(2)
+        }));
+        let response = block_on(handler.transmit(Command::new(0x00, 0xA4, 0x00, 0x00))).unwrap();
+
+        assert_eq!(StatusWord::FILE_NOT_FOUND, response.trailer);
+    }
+}
@@ -0,0 +1,180 @@
+use alloc::vec::Vec;
+
+use crate::{Command, EncodeError, LengthEncoding, Ne, ParseError, Response, StatusWord};
+
+/// An owned APDU command, that can be stored or sent across threads.
+/// Use [`CommandBuf::as_command`] to borrow it as [`Command`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommandBuf {
+    pub cla: u8,
+    pub ins: u8,
+    pub p1: u8,
+    pub p2: u8,
+    pub ne: Ne,
+    pub payload: Option<Vec<u8>>,
+    pub encoding: LengthEncoding,
+}
+
+impl CommandBuf {
+    /// Parses a serialised byte stream into an owned command.
+    pub fn parse(bytes: &[u8]) -> Result<Self, ParseError> {
+        Command::parse(bytes).map(Self::from)
+    }
+
+    /// Borrows the command as [`Command`].
+    pub fn as_command(&self) -> Command<'_> {
+        Command {
+            cla: self.cla,
+            ins: self.ins,
+            p1: self.p1,
+            p2: self.p2,
+            ne: self.ne,
+            payload: self.payload.as_deref(),
+            encoding: self.encoding,
+        }
+    }
+}
+
+impl<'a> From<Command<'a>> for CommandBuf {
+    fn from(command: Command<'a>) -> Self {
+        Self {
+            cla: command.cla,
+            ins: command.ins,
+            p1: command.p1,
+            p2: command.p2,
+            ne: command.ne,
+            payload: command.payload.map(<[u8]>::to_vec),
+            encoding: command.encoding,
+        }
+    }
+}
+
+impl<'a> From<&'a CommandBuf> for Command<'a> {
+    fn from(command: &'a CommandBuf) -> Self {
+        command.as_command()
+    }
+}
+
+impl TryFrom<CommandBuf> for Vec<u8> {
+    type Error = EncodeError;
+
+    /// Converts the command into octets, failing if the command cannot be encoded.
+    fn try_from(command: CommandBuf) -> Result<Self, Self::Error> {
+        command.as_command().to_vec()
+    }
+}
+
+/// An owned APDU response, that can be stored or sent across threads.
+/// Use [`ResponseBuf::as_response`] to borrow it as [`Response`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ResponseBuf {
+    pub payload: Vec<u8>,
+    pub trailer: StatusWord,
+}
+
+impl ResponseBuf {
+    /// Creates an empty response.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Borrows the response as [`Response`].
+    pub fn as_response(&self) -> Response<'_> {
+        Response {
+            payload: &self.payload,
+            trailer: self.trailer,
+        }
+    }
+
+    /// Determines whether the response indicates success or not.
+    pub fn is_ok(&self) -> bool {
+        self.as_response().is_ok()
+    }
+}
+
+impl<'a> From<Response<'a>> for ResponseBuf {
+    fn from(response: Response<'a>) -> Self {
+        Self {
+            payload: response.payload.to_vec(),
+            trailer: response.trailer,
+        }
+    }
+}
+
+impl<'a> From<&'a ResponseBuf> for Response<'a> {
+    fn from(response: &'a ResponseBuf) -> Self {
+        response.as_response()
+    }
+}
+
+impl From<&[u8]> for ResponseBuf {
+    fn from(bytes: &[u8]) -> Self {
+        Response::from(bytes).into()
+    }
+}
+
+impl From<Vec<u8>> for ResponseBuf {
+    /// Splits the trailer off the octets, reusing the allocation for the payload.
+    fn from(mut bytes: Vec<u8>) -> Self {
+        let trailer = match bytes.len() {
+            len if len < 2 => StatusWord::default(),
+            len => {
+                let trailer = StatusWord::new(bytes[len - 2], bytes[len - 1]);
+                bytes.truncate(len - 2);
+                trailer
+            }
+        };
+
+        Self {
+            payload: bytes,
+            trailer,
+        }
+    }
+}
+
+impl From<ResponseBuf> for Vec<u8> {
+    /// Converts the response into octets.
+    fn from(mut response: ResponseBuf) -> Self {
+        response
+            .payload
+            .extend_from_slice(&response.trailer.to_bytes());
+        response.payload
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    fn build(payload: &[u8]) -> CommandBuf {
+        Command::new_with_payload_le(0x00, 0xA4, 0x04, 0x00, 0x10, payload).into()
+    }
+
+    #[test]
+    fn command_buf_round_trip() {
+        let command = build(&[0x01, 0x02]);
+        let bytes: Vec<u8> = command.clone().try_into().unwrap();
+
+        assert_eq!(vec![0x00, 0xA4, 0x04, 0x00, 0x02, 0x01, 0x02, 0x10], bytes);
+        assert_eq!(
+            command
+                .as_command()
+                .with_encoding(LengthEncoding::ForceShort),
+            CommandBuf::parse(&bytes).unwrap().as_command(),
+        );
+    }
+
+    #[test]
+    fn response_buf_round_trip() {
+        let response = ResponseBuf::from(vec![0x01, 0x02, 0x90, 0x00]);
+
+        assert!(response.is_ok());
+        assert_eq!(vec![0x01, 0x02], response.payload);
+        assert_eq!(
+            vec![0x01, 0x02, 0x90, 0x00],
+            Vec::from(ResponseBuf::from(response.as_response())),
+        );
+    }
+}
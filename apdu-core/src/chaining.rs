@@ -0,0 +1,239 @@
+use crate::command::SHORT_COMMAND_MAX;
+use crate::{Command, HandleError, HandlerInCtx, Layer, LengthEncoding, Ne, Result};
+
+/// Default maximum length of the data field in each chained command
+const DEFAULT_CHUNK_LEN: usize = 0xFF;
+
+/// A layer to split commands with long payloads into chained commands
+#[derive(Clone, Copy, Debug)]
+pub struct ChainingLayer {
+    chunk_len: usize,
+}
+
+impl ChainingLayer {
+    /// Creates a layer splitting payloads longer than 255 bytes.
+    pub fn new() -> Self {
+        Self {
+            chunk_len: DEFAULT_CHUNK_LEN,
+        }
+    }
+
+    /// Sets the maximum length of the data field in each chained command, from 1 to 255.
+    pub fn with_chunk_len(mut self, chunk_len: usize) -> Self {
+        self.chunk_len = chunk_len.clamp(1, DEFAULT_CHUNK_LEN);
+        self
+    }
+}
+
+impl Default for ChainingLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<H> Layer<H> for ChainingLayer {
+    type Handler = Chaining<H>;
+
+    fn layer(&self, inner: H) -> Self::Handler {
+        Chaining {
+            inner,
+            chunk_len: self.chunk_len,
+        }
+    }
+}
+
+/// An handler that splits a command with a long payload into short commands chained with the CLA.
+/// Each command but the last has the chaining bit set, and must be answered with `9000`.
+/// Otherwise the response is returned as is, without sending the rest.
+/// As the chained commands are short, commands expecting more than 256 bytes are refused
+/// with [`HandleError::Protocol`] before sending any of them.
+/// Commands with short payloads, in proprietary classes or malformed are passed through as is.
+#[derive(Clone, Copy, Debug)]
+pub struct Chaining<H> {
+    inner: H,
+    chunk_len: usize,
+}
+
+impl<H> Chaining<H> {
+    /// Wraps the handler, splitting payloads longer than 255 bytes.
+    pub fn new(inner: H) -> Self {
+        ChainingLayer::new().layer(inner)
+    }
+
+    /// Sets the maximum length of the data field in each chained command, from 1 to 255.
+    pub fn with_chunk_len(mut self, chunk_len: usize) -> Self {
+        self.chunk_len = chunk_len.clamp(1, DEFAULT_CHUNK_LEN);
+        self
+    }
+
+    /// Returns the inner handler.
+    pub fn into_inner(self) -> H {
+        self.inner
+    }
+}
+
+impl<Ctx: Clone, H: HandlerInCtx<Ctx>> HandlerInCtx<Ctx> for Chaining<H> {
+    fn handle_in_ctx(&self, ctx: Ctx, command: &[u8], response: &mut [u8]) -> Result {
+        let parsed = match Command::parse(command) {
+            Ok(parsed) => parsed,
+            _ => return self.inner.handle_in_ctx(ctx, command, response),
+        };
+
+        let (payload, chaining) = match (parsed.payload, parsed.class().with_chaining(true)) {
+            (Some(payload), Ok(chaining)) if payload.len() > self.chunk_len => (payload, chaining),
+            _ => return self.inner.handle_in_ctx(ctx, command, response),
+        };
+
+        // The last command must not have the chaining bit, even if the caller had set it.
+        let class = chaining.with_chaining(false).unwrap_or(chaining);
+
+        if !matches!(parsed.expected_len(), Ok(n) if n <= 0x100) {
+            return Err(HandleError::Protocol(
+                "chained commands cannot expect more than 256 bytes",
+            ));
+        }
+
+        let mut len = 0;
+        let mut chunks = payload.chunks(self.chunk_len).peekable();
+        while let Some(chunk) = chunks.next() {
+            let last = chunks.peek().is_none();
+            let chunk = Command {
+                payload: Some(chunk),
+                encoding: LengthEncoding::ForceShort,
+                ..parsed.clone()
+            };
+
+            // Only the last command expects the response data.
+            let chunk = match last {
+                true => chunk.with_class(class).with_ne(parsed.ne),
+                _ => chunk.with_class(chaining).with_ne(Ne::None),
+            };
+
+            let bytes = chunk
+                .to_array::<SHORT_COMMAND_MAX>()
+                .expect("chunks always fit in a short command");
+            len = self.inner.handle_in_ctx(ctx.clone(), &bytes, response)?;
+
+            if !matches!(response[..len], [.., 0x90, 0x00]) {
+                break;
+            }
+        }
+
+        Ok(len)
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::mock::MockCard;
+    use crate::{Class, Handler, HandlerBuilder};
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    /// A card that answers with the trailer given, or `9000` to chained commands.
+    fn card(trailer: [u8; 2]) -> MockCard<impl Fn(&[u8]) -> Vec<u8>> {
+        MockCard::new(move |command| match Class::from(command[0]).is_chaining() {
+            true => vec![0x90, 0x00],
+            _ => trailer.to_vec(),
+        })
+    }
+
+    #[test]
+    fn chain_commands() {
+        let payload: Vec<u8> = (0..=0xFF).collect();
+        let command = Command::new_with_payload_le(0x01, 0xDB, 0x3F, 0xFF, 0x100, &payload);
+
+        let handler = HandlerBuilder::new()
+            .layer(ChainingLayer::new().with_chunk_len(100))
+            .handler(card([0x90, 0x00]));
+
+        let mut buf = [0u8; 2];
+        assert_eq!(
+            2,
+            handler
+                .handle(&command.to_vec().unwrap(), &mut buf)
+                .unwrap()
+        );
+        assert_eq!(
+            vec![
+                [&[0x11, 0xDB, 0x3F, 0xFF, 100][..], &payload[..100]].concat(),
+                [&[0x11, 0xDB, 0x3F, 0xFF, 100][..], &payload[100..200]].concat(),
+                [&[0x01, 0xDB, 0x3F, 0xFF, 56][..], &payload[200..], &[0x00]].concat(),
+            ],
+            *handler.into_inner().commands.borrow(),
+        );
+    }
+
+    #[test]
+    fn clear_chaining_on_last() {
+        let handler = Chaining::new(card([0x90, 0x00])).with_chunk_len(2);
+        let command = Command::new_with_payload(0x10, 0xDB, 0x3F, 0xFF, &[0x01, 0x02, 0x03])
+            .to_vec()
+            .unwrap();
+
+        let mut buf = [0u8; 2];
+        handler.handle(&command, &mut buf).unwrap();
+        assert_eq!(
+            vec![
+                vec![0x10, 0xDB, 0x3F, 0xFF, 0x02, 0x01, 0x02],
+                vec![0x00, 0xDB, 0x3F, 0xFF, 0x01, 0x03],
+            ],
+            *handler.into_inner().commands.borrow(),
+        );
+    }
+
+    #[test]
+    fn refuse_long_ne() {
+        let handler = Chaining::new(card([0x90, 0x00]));
+        let command = Command::new_with_payload_le(0x00, 0xDB, 0x3F, 0xFF, 0x1000, &[0x01; 0x300])
+            .to_vec()
+            .unwrap();
+
+        let mut buf = [0u8; 2];
+        assert!(matches!(
+            handler.handle(&command, &mut buf),
+            Err(HandleError::Protocol(_))
+        ));
+        assert!(handler.into_inner().commands.borrow().is_empty());
+    }
+
+    #[test]
+    fn refuse_extended_maximum_ne() {
+        let handler = Chaining::new(card([0x90, 0x00]));
+        let mut command = vec![0x00, 0xDB, 0x3F, 0xFF, 0x00, 0x03, 0x00];
+        command.extend_from_slice(&[0x01; 0x300]);
+        command.extend_from_slice(&[0x00, 0x00]);
+
+        let mut buf = [0u8; 2];
+        let err = handler.handle(&command, &mut buf).unwrap_err();
+        assert!(matches!(err, HandleError::Protocol(_)));
+        assert!(!err.is_retryable());
+        assert!(handler.into_inner().commands.borrow().is_empty());
+    }
+
+    #[test]
+    fn pass_through() {
+        let handler = Chaining::new(card([0x90, 0x00]));
+        let command = Command::new_with_payload(0x00, 0xDA, 0x00, 0x00, &[0x01; 0xFF])
+            .to_vec()
+            .unwrap();
+
+        let mut buf = [0u8; 2];
+        handler.handle(&command, &mut buf).unwrap();
+        assert_eq!(vec![command], *handler.into_inner().commands.borrow());
+    }
+
+    #[test]
+    fn stop_on_error() {
+        let handler = Chaining::new(MockCard::new(|_| vec![0x68, 0x83]));
+        let command = Command::new_with_payload(0x00, 0xDA, 0x00, 0x00, &[0x01; 0x300])
+            .to_vec()
+            .unwrap();
+
+        let mut buf = [0u8; 2];
+        assert_eq!(2, handler.handle(&command, &mut buf).unwrap());
+        assert_eq!([0x68, 0x83], buf);
+        assert_eq!(1, handler.into_inner().commands.borrow().len());
+    }
+}
@@ -0,0 +1,259 @@
+use crate::ClassError;
+
+/// Mask of the command chaining bit in interindustry classes
+const CHAINING: u8 = 0x10;
+
+/// An indication of secure messaging in the class byte
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SecureMessaging {
+    /// No SM or no indication.
+    #[default]
+    None,
+
+    /// Proprietary SM format.
+    Proprietary,
+
+    /// SM according to ISO/IEC 7816-4, command header not processed.
+    HeaderNotProcessed,
+
+    /// SM according to ISO/IEC 7816-4, command header authenticated.
+    HeaderAuthenticated,
+}
+
+/// A category of the class byte, defined in ISO/IEC 7816-4, 5.4.1
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ClassKind {
+    /// `000x xxxx`: Logical channels 0 to 3.
+    FirstInterindustry,
+
+    /// `01xx xxxx`: Logical channels 4 to 19.
+    FurtherInterindustry,
+
+    /// `001x xxxx`: Reserved for future use.
+    Reserved,
+
+    /// `1xxx xxxx` other than `FF`: Proprietary class.
+    Proprietary,
+
+    /// `FF`: Invalid, reserved for PPS.
+    Invalid,
+}
+
+/// A class byte (CLA) of APDU commands
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Class(pub u8);
+
+impl Class {
+    /// Constructs an interindustry class with the logical channel, SM indication, and command chaining.
+    /// Channels 0 to 3 are encoded in the first interindustry class, 4 to 19 in the further one.
+    pub fn new(
+        channel: u8,
+        secure_messaging: SecureMessaging,
+        chaining: bool,
+    ) -> Result<Self, ClassError> {
+        let chaining = match chaining {
+            true => CHAINING,
+            _ => 0,
+        };
+
+        match channel {
+            0..=3 => {
+                let sm = match secure_messaging {
+                    SecureMessaging::None => 0x00,
+                    SecureMessaging::Proprietary => 0x04,
+                    SecureMessaging::HeaderNotProcessed => 0x08,
+                    SecureMessaging::HeaderAuthenticated => 0x0C,
+                };
+
+                Ok(Self(chaining | sm | channel))
+            }
+            4..=19 => {
+                let sm = match secure_messaging {
+                    SecureMessaging::None => 0x00,
+                    SecureMessaging::HeaderNotProcessed => 0x20,
+                    sm => return Err(ClassError::SecureMessagingNotSupported(sm)),
+                };
+
+                Ok(Self(0x40 | sm | chaining | (channel - 4)))
+            }
+            _ => Err(ClassError::ChannelOutOfRange(channel)),
+        }
+    }
+
+    /// Constructs a proprietary class.
+    pub fn proprietary(cla: u8) -> Result<Self, ClassError> {
+        match Self(cla).kind() {
+            ClassKind::Proprietary => Ok(Self(cla)),
+            _ => Err(ClassError::NotProprietary(cla)),
+        }
+    }
+
+    /// Determines the category of the class.
+    pub const fn kind(self) -> ClassKind {
+        match self.0 {
+            0x00..=0x1F => ClassKind::FirstInterindustry,
+            0x20..=0x3F => ClassKind::Reserved,
+            0x40..=0x7F => ClassKind::FurtherInterindustry,
+            0xFF => ClassKind::Invalid,
+            _ => ClassKind::Proprietary,
+        }
+    }
+
+    /// Determines whether the class is interindustry or not.
+    pub const fn is_interindustry(self) -> bool {
+        matches!(
+            self.kind(),
+            ClassKind::FirstInterindustry | ClassKind::FurtherInterindustry
+        )
+    }
+
+    /// Returns the logical channel number, or None if the class is not interindustry.
+    pub const fn channel(self) -> Option<u8> {
+        match self.kind() {
+            ClassKind::FirstInterindustry => Some(self.0 & 0x03),
+            ClassKind::FurtherInterindustry => Some((self.0 & 0x0F) + 4),
+            _ => None,
+        }
+    }
+
+    /// Returns the SM indication, or None if the class is not interindustry.
+    pub const fn secure_messaging(self) -> Option<SecureMessaging> {
+        match self.kind() {
+            ClassKind::FirstInterindustry => Some(match self.0 & 0x0C {
+                0x00 => SecureMessaging::None,
+                0x04 => SecureMessaging::Proprietary,
+                0x08 => SecureMessaging::HeaderNotProcessed,
+                _ => SecureMessaging::HeaderAuthenticated,
+            }),
+            ClassKind::FurtherInterindustry => Some(match self.0 & 0x20 {
+                0x00 => SecureMessaging::None,
+                _ => SecureMessaging::HeaderNotProcessed,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Determines whether the command is not the last command of a chain.
+    /// Always false if the class is not interindustry.
+    pub const fn is_chaining(self) -> bool {
+        self.is_interindustry() && self.0 & CHAINING != 0
+    }
+
+    /// Returns the class with the logical channel replaced, keeping SM indication and command chaining.
+    pub fn with_channel(self, channel: u8) -> Result<Self, ClassError> {
+        match self.secure_messaging() {
+            Some(sm) => Self::new(channel, sm, self.is_chaining()),
+            _ => Err(ClassError::NotInterindustry(self.0)),
+        }
+    }
+
+    /// Returns the class with the SM indication replaced, keeping logical channel and command chaining.
+    pub fn with_secure_messaging(
+        self,
+        secure_messaging: SecureMessaging,
+    ) -> Result<Self, ClassError> {
+        match self.channel() {
+            Some(channel) => Self::new(channel, secure_messaging, self.is_chaining()),
+            _ => Err(ClassError::NotInterindustry(self.0)),
+        }
+    }
+
+    /// Returns the class with the command chaining bit set or cleared.
+    pub fn with_chaining(self, chaining: bool) -> Result<Self, ClassError> {
+        match (self.is_interindustry(), chaining) {
+            (false, _) => Err(ClassError::NotInterindustry(self.0)),
+            (_, true) => Ok(Self(self.0 | CHAINING)),
+            _ => Ok(Self(self.0 & !CHAINING)),
+        }
+    }
+}
+
+impl From<u8> for Class {
+    fn from(cla: u8) -> Self {
+        Self(cla)
+    }
+}
+
+impl From<Class> for u8 {
+    fn from(class: Class) -> Self {
+        class.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_interindustry() {
+        let class = Class::new(3, SecureMessaging::HeaderAuthenticated, true).unwrap();
+
+        assert_eq!(Class(0x1F), class);
+        assert_eq!(ClassKind::FirstInterindustry, class.kind());
+        assert_eq!(Some(3), class.channel());
+        assert_eq!(
+            Some(SecureMessaging::HeaderAuthenticated),
+            class.secure_messaging()
+        );
+        assert!(class.is_chaining());
+        assert_eq!(Class(0x0F), class.with_chaining(false).unwrap());
+    }
+
+    #[test]
+    fn further_interindustry() {
+        let class = Class::new(19, SecureMessaging::HeaderNotProcessed, true).unwrap();
+
+        assert_eq!(Class(0x7F), class);
+        assert_eq!(ClassKind::FurtherInterindustry, class.kind());
+        assert_eq!(Some(19), class.channel());
+        assert_eq!(
+            Some(SecureMessaging::HeaderNotProcessed),
+            class.secure_messaging()
+        );
+        assert!(class.is_chaining());
+
+        assert_eq!(
+            Err(ClassError::SecureMessagingNotSupported(
+                SecureMessaging::Proprietary
+            )),
+            Class::new(4, SecureMessaging::Proprietary, false),
+        );
+        assert_eq!(
+            Err(ClassError::ChannelOutOfRange(20)),
+            Class::new(20, SecureMessaging::None, false),
+        );
+    }
+
+    #[test]
+    fn switch_channels() {
+        let class = Class(0x08).with_chaining(true).unwrap();
+
+        assert_eq!(Class(0x1A), class.with_channel(2).unwrap());
+        assert_eq!(Class(0x71), class.with_channel(5).unwrap());
+        assert_eq!(
+            Class(0x10),
+            Class(0x71)
+                .with_channel(0)
+                .unwrap()
+                .with_secure_messaging(SecureMessaging::None)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn non_interindustry() {
+        assert_eq!(ClassKind::Reserved, Class(0x20).kind());
+        assert_eq!(ClassKind::Invalid, Class(0xFF).kind());
+        assert_eq!(Ok(Class(0x80)), Class::proprietary(0x80));
+        assert_eq!(
+            Err(ClassError::NotProprietary(0x00)),
+            Class::proprietary(0x00)
+        );
+        assert_eq!(None, Class(0x80).channel());
+        assert!(!Class(0x90).is_chaining());
+        assert_eq!(
+            Err(ClassError::NotInterindustry(0x80)),
+            Class(0x80).with_channel(1)
+        );
+    }
+}
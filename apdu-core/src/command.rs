@@ -1,12 +1,136 @@
+use crate::{
+    tlv, ArrayBuf, Class, ClassKind, EncodeError, ParseError, SecureMessaging, Sink, Violation,
+};
+
+/// Maximum length of the data field that can be expressed in short Lc
+const SHORT_LC_MAX: usize = 0xFF;
+
+/// Maximum number of expected bytes that can be expressed in short Le
+const SHORT_LE_MAX: u32 = 0x100;
+
+/// Maximum number of expected bytes that can be expressed in extended Le
+const EXTENDED_LE_MAX: u32 = 0x10000;
+
+/// Maximum length of the data field that can be expressed in extended Lc
+const EXTENDED_LC_MAX: usize = 0xFFFF;
+
+/// Maximum length of a short command, that is header, Lc, data field and Le
+pub(crate) const SHORT_COMMAND_MAX: usize = 4 + 1 + SHORT_LC_MAX + 1;
+
+/// A policy to choose the format of length fields (Lc and Le) on encoding
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LengthEncoding {
+    /// Uses short format if possible, otherwise extended format.
+    #[default]
+    Auto,
+
+    /// Always uses short format, failing if the lengths do not fit in it.
+    /// Suitable for cards that do not support extended length.
+    ForceShort,
+
+    /// Always uses extended format.
+    ForceExtended,
+}
+
+/// A case of APDU commands, defined in ISO/IEC 7816-3, 12.1.3
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Case {
+    /// No command data, no response data.
+    Case1,
+
+    /// No command data, response data expected with short Le.
+    Case2Short,
+
+    /// Command data with short Lc, no response data.
+    Case3Short,
+
+    /// Command data with short Lc, response data expected with short Le.
+    Case4Short,
+
+    /// No command data, response data expected with extended Le.
+    Case2Extended,
+
+    /// Command data with extended Lc, no response data.
+    Case3Extended,
+
+    /// Command data with extended Lc, response data expected with extended Le.
+    Case4Extended,
+}
+
+impl Case {
+    /// Determines whether the command has the data field (Lc) or not.
+    pub const fn has_data(self) -> bool {
+        matches!(
+            self,
+            Self::Case3Short | Self::Case4Short | Self::Case3Extended | Self::Case4Extended
+        )
+    }
+
+    /// Determines whether the command expects response data (Le) or not.
+    pub const fn expects_data(self) -> bool {
+        matches!(
+            self,
+            Self::Case2Short | Self::Case4Short | Self::Case2Extended | Self::Case4Extended
+        )
+    }
+
+    /// Determines whether the length fields are encoded in extended format or not.
+    pub const fn is_extended(self) -> bool {
+        matches!(
+            self,
+            Self::Case2Extended | Self::Case3Extended | Self::Case4Extended
+        )
+    }
+}
+
+/// Maximum number of bytes expected in the response data field (Ne)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Ne {
+    /// No response data is expected, so Le field is absent.
+    #[default]
+    None,
+
+    /// Up to the exact number of bytes, from 1 to 65536.
+    Exact(u32),
+
+    /// Maximum available: up to 256 bytes in short format, or 65536 bytes in extended format.
+    Maximum,
+}
+
+impl Ne {
+    /// Determines whether response data is expected or not.
+    pub const fn is_some(self) -> bool {
+        !matches!(self, Self::None)
+    }
+}
+
+impl From<u16> for Ne {
+    /// Converts from the value of Le field, where `0` means the maximum.
+    fn from(le: u16) -> Self {
+        match le {
+            0 => Self::Maximum,
+            n => Self::Exact(n.into()),
+        }
+    }
+}
+
+impl From<u8> for Ne {
+    /// Converts from the value of short Le field, where `0` means the maximum.
+    fn from(le: u8) -> Self {
+        Ne::from(u16::from(le))
+    }
+}
+
 /// An APDU command to be transmitted
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Command<'a> {
     pub cla: u8,
     pub ins: u8,
     pub p1: u8,
     pub p2: u8,
-    pub le: Option<u16>,
+    pub ne: Ne,
     pub payload: Option<&'a [u8]>,
+    pub encoding: LengthEncoding,
 }
 
 impl<'a> Command<'a> {
@@ -18,21 +142,23 @@ impl<'a> Command<'a> {
             ins,
             p1,
             p2,
-            le: None,
+            ne: Ne::None,
             payload: None,
+            encoding: LengthEncoding::Auto,
         }
     }
 
     /// Constructs an command with CLA, INS, P1, P2, and Le.
-    /// A payload will be received.
+    /// A payload will be received. Le of `0` means the maximum, see [`Ne`].
     pub fn new_with_le(cla: u8, ins: u8, p1: u8, p2: u8, le: u16) -> Self {
         Self {
             cla,
             ins,
             p1,
             p2,
-            le: Some(le),
+            ne: le.into(),
             payload: None,
+            encoding: LengthEncoding::Auto,
         }
     }
 
@@ -44,13 +170,14 @@ impl<'a> Command<'a> {
             ins,
             p1,
             p2,
-            le: None,
+            ne: Ne::None,
             payload: Some(payload),
+            encoding: LengthEncoding::Auto,
         }
     }
 
     /// Constructs an command with CLA, INS, P1, P2, Le, and a payload.
-    /// A payload will be received.
+    /// A payload will be received. Le of `0` means the maximum, see [`Ne`].
     pub fn new_with_payload_le(
         cla: u8,
         ins: u8,
@@ -64,137 +191,858 @@ impl<'a> Command<'a> {
             ins,
             p1,
             p2,
-            le: Some(le),
+            ne: le.into(),
             payload: Some(payload),
+            encoding: LengthEncoding::Auto,
         }
     }
 
-    /// Writes a serialised byte stream onto the mutable buffer.
-    pub fn write(&self, buf: &mut [u8]) {
-        let Command {
+    /// Constructs an command of the case with CLA, INS, P1, P2, and the parts the case requires.
+    /// Fails if the parts do not match with the case.
+    pub fn new_with_case(
+        case: Case,
+        cla: u8,
+        ins: u8,
+        p1: u8,
+        p2: u8,
+        payload: Option<&'a [u8]>,
+        ne: Ne,
+    ) -> Result<Self, EncodeError> {
+        let command = Self {
             cla,
             ins,
             p1,
             p2,
-            le,
+            ne,
             payload,
-        } = self;
+            encoding: match case {
+                Case::Case1 => LengthEncoding::Auto,
+                _ if case.is_extended() => LengthEncoding::ForceExtended,
+                _ => LengthEncoding::ForceShort,
+            },
+        };
+
+        match command.case()? {
+            actual if actual == case => Ok(command),
+            actual => Err(EncodeError::CaseMismatch {
+                expected: case,
+                actual,
+            }),
+        }
+    }
+
+    /// Returns the class byte (CLA) of the command.
+    pub fn class(&self) -> Class {
+        self.cla.into()
+    }
+
+    /// Sets the class byte (CLA) of the command.
+    pub fn with_class(mut self, class: Class) -> Self {
+        self.cla = class.into();
+        self
+    }
+
+    /// Sets the maximum number of bytes expected in the response.
+    pub fn with_ne(mut self, ne: Ne) -> Self {
+        self.ne = ne;
+        self
+    }
 
-        // &mut [u8] does not have push or extend methods,
-        // So we are using a mutator to have a reference of the buffer and a cursor on them.
-        struct Mutator<'a> {
-            buf: &'a mut [u8],
-            i: usize,
+    /// Sets the policy to choose the format of length fields.
+    pub fn with_encoding(mut self, encoding: LengthEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Determines the case of the command, following the policy of length fields.
+    pub fn case(&self) -> Result<Case, EncodeError> {
+        let extended = self.is_extended()?;
+
+        Ok(match (self.data().is_some(), self.ne.is_some(), extended) {
+            (false, false, _) => Case::Case1,
+            (false, true, false) => Case::Case2Short,
+            (true, false, false) => Case::Case3Short,
+            (true, true, false) => Case::Case4Short,
+            (false, true, true) => Case::Case2Extended,
+            (true, false, true) => Case::Case3Extended,
+            (true, true, true) => Case::Case4Extended,
+        })
+    }
+
+    /// Determines whether the length fields are encoded in extended format, following the policy.
+    /// Extended Lc and Le always appear together, so either of them requiring it
+    /// turns the entire command into extended one (case 2E, 3E, or 4E).
+    pub fn is_extended(&self) -> Result<bool, EncodeError> {
+        let lc = self.data().map_or(0, |p| p.len());
+        if lc > EXTENDED_LC_MAX {
+            return Err(EncodeError::PayloadTooLong(lc));
         }
 
-        impl<'a> Mutator<'a> {
-            fn push(&mut self, b: u8) {
-                self.buf[self.i] = b;
-                self.i += 1;
+        let ne = match self.ne {
+            Ne::Exact(n) if n == 0 || n > EXTENDED_LE_MAX => {
+                return Err(EncodeError::NeOutOfRange(n))
             }
+            Ne::Exact(n) => n,
+            _ => 0,
+        };
+
+        let needs_extended = lc > SHORT_LC_MAX || ne > SHORT_LE_MAX;
+
+        match self.encoding {
+            LengthEncoding::Auto => Ok(needs_extended),
+            LengthEncoding::ForceExtended => Ok(true),
+            LengthEncoding::ForceShort if !needs_extended => Ok(false),
+            LengthEncoding::ForceShort if lc > SHORT_LC_MAX => Err(EncodeError::PayloadTooLong(lc)),
+            LengthEncoding::ForceShort => Err(EncodeError::LeTooLong(ne)),
+        }
+    }
+
+    /// Calculates the maximum number of bytes expected in the response data field,
+    /// resolving [`Ne::Maximum`] following the length format.
+    pub fn expected_len(&self) -> Result<usize, EncodeError> {
+        let extended = self.is_extended()?;
+
+        Ok(match self.ne {
+            Ne::None => 0,
+            Ne::Exact(n) => n as usize,
+            Ne::Maximum if extended => EXTENDED_LE_MAX as usize,
+            Ne::Maximum => SHORT_LE_MAX as usize,
+        })
+    }
+
+    /// Checks the command against ISO/IEC 7816 rules, returning the first violation found.
+    /// Use [`Command::violations`] to see all of them.
+    pub fn validate(&self) -> Result<(), Violation> {
+        match self.violations().next() {
+            Some(violation) => Err(violation),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks the command against ISO/IEC 7816 rules, iterating over all the violations found.
+    pub fn violations(&self) -> impl Iterator<Item = Violation> {
+        let class = self.class();
+        let data = self.data();
 
-            fn extend(&mut self, b: &[u8]) {
-                let len = b.len();
-                self.buf[self.i..self.i + len].copy_from_slice(b);
-                self.i += len;
+        let cla = match class.kind() {
+            ClassKind::Invalid => Some(Violation::InvalidClass(self.cla)),
+            _ => None,
+        };
+
+        let ins = match self.ins & 0xF0 {
+            0x60 | 0x90 => Some(Violation::InvalidInstruction(self.ins)),
+            _ => None,
+        };
+
+        // Data field of odd instructions must be BER-TLV encoded, if present.
+        let tlv = match data {
+            Some(d) if self.ins & 0x01 == 1 && !d.is_empty() && !tlv::is_valid(d, |_| true) => {
+                Some(Violation::NotBerTlv(self.ins))
             }
+            _ => None,
+        };
+
+        let length = self.is_extended().err().map(Violation::Length);
+
+        // ISO secure messaging requires the data field to consist of SM data objects,
+        // which have context-specific tags.
+        let sm = match class.secure_messaging() {
+            Some(
+                sm @ (SecureMessaging::HeaderNotProcessed | SecureMessaging::HeaderAuthenticated),
+            ) if !data.is_some_and(|d| tlv::is_valid(d, |t| t[0] & 0xC0 == 0x80)) => {
+                Some(Violation::SecureMessagingConflict(sm))
+            }
+            _ => None,
+        };
+
+        [cla, ins, tlv, length, sm].into_iter().flatten()
+    }
+
+    /// Returns the command data field, or None if the data field is absent.
+    /// Empty payload is treated as absent, as Lc cannot be zero according to the spec.
+    fn data(&self) -> Option<&'a [u8]> {
+        self.payload.filter(|p| !p.is_empty())
+    }
+
+    /// Writes a serialised byte stream onto the mutable buffer, returning length of the data written.
+    /// Ne of 256 in short format and 65536 in extended format are encoded as zeros in Le field.
+    pub fn write(&self, buf: &mut [u8]) -> Result<usize, EncodeError> {
+        let len = self.len()?;
+        if buf.len() < len {
+            return Err(EncodeError::BufferTooShort {
+                required: len,
+                actual: buf.len(),
+            });
         }
 
-        let mut m = Mutator { buf, i: 0 };
-        m.extend(&[*cla, *ins, *p1, *p2]);
-
-        let has_payload = &payload.is_some();
-        if let Some(p) = payload {
-            // According to spec, length can be 0, 1 or 2 bytes
-            // 2 bytes is prefaced by 00 to differentiate between 1 byte lengths
-            if cfg!(feature = "longer_payloads") && p.len() > u8::MAX as usize {
-                m.push(0u8);
-                m.push(p.len() as u8);
-                m.push((p.len() >> 8) as u8);
+        self.write_to(&mut &mut buf[..len])
+    }
+
+    /// Writes a serialised byte stream onto the sink, returning length of the data written.
+    pub fn write_to<S: Sink + ?Sized>(&self, sink: &mut S) -> Result<usize, EncodeError> {
+        let len = self.len()?;
+        let extended = self.is_extended()?;
+
+        sink.write_bytes(&[self.cla, self.ins, self.p1, self.p2])?;
+
+        let data = self.data();
+        if let Some(p) = data {
+            // According to spec, Lc is 1 byte in short format,
+            // or 3 bytes prefaced by 00 in extended format (big-endian).
+            if extended {
+                let [lc1, lc2] = (p.len() as u16).to_be_bytes();
+                sink.write_bytes(&[0x00, lc1, lc2])?;
             } else {
-                m.push(p.len() as u8);
+                sink.write_bytes(&[p.len() as u8])?;
             }
 
-            m.extend(p);
+            sink.write_bytes(p)?;
         }
 
-        if let Some(l) = *le {
-            if cfg!(feature = "longer_payloads") && l > u8::MAX.into() {
-                if !has_payload {
-                    m.push(0u8);
+        let le = match self.ne {
+            Ne::None => None,
+            Ne::Exact(n) => Some(n),
+            Ne::Maximum => Some(0),
+        };
+
+        if let Some(l) = le {
+            // Extended Le is 2 bytes (big-endian) following the extended Lc,
+            // or 3 bytes prefaced by 00 if Lc is absent.
+            // The maximum (256 or 65536) is truncated to zeros here.
+            if extended {
+                if data.is_none() {
+                    sink.write_bytes(&[0x00])?;
                 }
-                m.push(l as u8);
-                m.push((l >> 8) as u8);
+                sink.write_bytes(&(l as u16).to_be_bytes())?;
             } else {
-                m.push(l as u8);
+                sink.write_bytes(&[l as u8])?;
             }
         }
+
+        Ok(len)
+    }
+
+    /// Writes a serialised byte stream onto the buffer, returning length of the data written.
+    #[cfg(feature = "bytes")]
+    pub fn put<B: bytes::BufMut + ?Sized>(&self, buf: &mut B) -> Result<usize, EncodeError> {
+        self.write_to(&mut crate::sink::BufMutSink(buf))
+    }
+
+    /// Serialises the command into a buffer on the stack, without any allocator.
+    pub fn to_array<const N: usize>(&self) -> Result<ArrayBuf<N>, EncodeError> {
+        let len = self.len()?;
+        if N < len {
+            return Err(EncodeError::BufferTooShort {
+                required: len,
+                actual: N,
+            });
+        }
+
+        let mut buf = ArrayBuf::new();
+        self.write_to(&mut buf)?;
+
+        Ok(buf)
+    }
+
+    /// Serialises the command into octets.
+    #[cfg(feature = "alloc")]
+    pub fn to_vec(&self) -> Result<alloc::vec::Vec<u8>, EncodeError> {
+        let mut buf = alloc::vec::Vec::with_capacity(self.len()?);
+        self.write_to(&mut buf)?;
+
+        Ok(buf)
     }
 
     /// Calculates the length of entire the command.
     #[allow(clippy::len_without_is_empty)]
-    pub fn len(&self) -> usize {
-        let (lc, payload) = match self.payload {
-            Some(p) => (
-                match cfg!(feature = "longer_payloads") && p.len() > u8::MAX as usize {
-                    true => 2,
-                    _ => 1,
-                },
-                p.len(),
-            ),
+    pub fn len(&self) -> Result<usize, EncodeError> {
+        let extended = self.is_extended()?;
+        let data = self.data();
+
+        let (lc, payload) = match data {
+            Some(p) => (if extended { 3 } else { 1 }, p.len()),
             _ => (0, 0),
         };
 
-        let le = match self.le {
-            Some(l) => match cfg!(feature = "longer_payloads") && l > u8::MAX.into() {
-                true => match self.payload.is_some() {
-                    true => 2,
-                    _ => 3,
-                },
+        let le = match self.ne {
+            Ne::Exact(_) | Ne::Maximum => match (extended, data.is_some()) {
+                (true, true) => 2,
+                (true, false) => 3,
                 _ => 1,
             },
             _ => 0,
         };
 
         // Header is always 4 bytes
-        4 + lc + payload + le
+        Ok(4 + lc + payload + le)
     }
 }
 
-#[cfg(feature = "std")]
-impl<'a> From<Command<'a>> for Vec<u8> {
-    /// Converts the command into octets.
-    fn from(command: Command) -> Self {
-        let len = command.len();
-        let mut buf = Vec::with_capacity(len);
+impl<'a> Command<'a> {
+    /// Parses a serialised byte stream into a command.
+    /// All cases of ISO/IEC 7816-3 (1, 2S, 3S, 4S, 2E, 3E, and 4E) are recognised,
+    /// and the length format of the stream is kept in [`Command::encoding`].
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, ParseError> {
+        let (header, body) = match bytes.len() {
+            len if len < 4 => return Err(ParseError::TooShort(len)),
+            _ => bytes.split_at(4),
+        };
+
+        let mut command = Self::new(header[0], header[1], header[2], header[3]);
 
-        #[allow(clippy::uninit_vec)]
-        unsafe {
-            buf.set_len(len);
+        match *body {
+            // Case 1
+            [] => {}
+
+            // Case 2S
+            [le] => {
+                command.ne = u16::from(le).into();
+                command.encoding = LengthEncoding::ForceShort;
+            }
+
+            // Case 2E
+            [0x00, le1, le2] => {
+                command.ne = u16::from_be_bytes([le1, le2]).into();
+                command.encoding = LengthEncoding::ForceExtended;
+            }
+
+            // Case 3E or 4E
+            [0x00, lc1, lc2, ref rest @ ..] if !rest.is_empty() => {
+                let lc = u16::from_be_bytes([lc1, lc2]) as usize;
+                if lc == 0 {
+                    return Err(ParseError::ZeroLc);
+                }
+
+                let (payload, le) = match rest.len().checked_sub(lc) {
+                    Some(0) => (rest, None),
+                    Some(2) => {
+                        let (payload, le) = rest.split_at(lc);
+                        (payload, Some(le))
+                    }
+                    _ => {
+                        return Err(ParseError::LengthMismatch {
+                            lc,
+                            actual: rest.len(),
+                        })
+                    }
+                };
+                command.payload = Some(payload);
+                command.ne = le.map_or(Ne::None, |l| u16::from_be_bytes([l[0], l[1]]).into());
+                command.encoding = LengthEncoding::ForceExtended;
+            }
+
+            // Case 3S or 4S
+            [lc, ref rest @ ..] if lc != 0x00 => {
+                let lc = lc as usize;
+                let (payload, le) = match rest.len().checked_sub(lc) {
+                    Some(0) => (rest, None),
+                    Some(1) => (&rest[..lc], Some(u16::from(rest[lc]))),
+                    _ => {
+                        return Err(ParseError::LengthMismatch {
+                            lc,
+                            actual: rest.len(),
+                        })
+                    }
+                };
+                command.payload = Some(payload);
+                command.ne = le.map_or(Ne::None, Ne::from);
+                command.encoding = LengthEncoding::ForceShort;
+            }
+
+            _ => return Err(ParseError::Malformed(body.len())),
         }
 
-        command.write(&mut buf);
+        Ok(command)
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for Command<'a> {
+    type Error = ParseError;
+
+    fn try_from(bytes: &'a [u8]) -> Result<Self, Self::Error> {
+        Self::parse(bytes)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> TryFrom<Command<'a>> for alloc::vec::Vec<u8> {
+    type Error = EncodeError;
 
-        buf
+    /// Converts the command into octets, failing if the command cannot be encoded.
+    fn try_from(command: Command) -> Result<Self, Self::Error> {
+        command.to_vec()
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "alloc"))]
 mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
     use super::*;
 
     #[test]
     fn command_to_vec() {
         assert_eq!(
             vec![0x01, 0x02, 0x03, 0x04, 0x03, 0x05, 0x06, 0x07, 0x08],
-            Vec::from(Command::new_with_payload_le(
+            Vec::try_from(Command::new_with_payload_le(
                 0x01,
                 0x02,
                 0x03,
                 0x04,
                 0x08,
                 &[0x05, 0x06, 0x07]
+            ))
+            .unwrap(),
+        );
+        assert_eq!(
+            Err(EncodeError::PayloadTooLong(0x10000)),
+            Vec::try_from(Command::new_with_payload(
+                0x00,
+                0xD6,
+                0x00,
+                0x00,
+                &[0x00; 0x10000]
             )),
         );
     }
+
+    fn assert_encoded(command: Command, expected: &[u8]) {
+        assert_eq!(Ok(expected.len()), command.len(), "{command:?}");
+        assert_eq!(expected, command.to_vec().unwrap().as_slice());
+    }
+
+    /// Examples of each case from ISO/IEC 7816-3, 12.1.3 (short length fields).
+    #[test]
+    fn short_cases() {
+        let data = [0xAA; 0xFF];
+
+        // Case 1
+        assert_encoded(
+            Command::new(0x00, 0xA4, 0x04, 0x00),
+            &[0x00, 0xA4, 0x04, 0x00],
+        );
+
+        // Case 2S
+        assert_encoded(
+            Command::new_with_le(0x00, 0xB0, 0x00, 0x00, 0x10),
+            &[0x00, 0xB0, 0x00, 0x00, 0x10],
+        );
+        assert_encoded(
+            Command::new_with_le(0x00, 0xB0, 0x00, 0x00, 0x100),
+            &[0x00, 0xB0, 0x00, 0x00, 0x00],
+        );
+        assert_encoded(
+            Command::new_with_le(0x00, 0xB0, 0x00, 0x00, 0),
+            &[0x00, 0xB0, 0x00, 0x00, 0x00],
+        );
+
+        // Case 3S
+        assert_encoded(
+            Command::new_with_payload(0x00, 0xA4, 0x04, 0x00, &[0x01, 0x02]),
+            &[0x00, 0xA4, 0x04, 0x00, 0x02, 0x01, 0x02],
+        );
+        assert_encoded(
+            Command::new_with_payload(0x00, 0xD6, 0x00, 0x00, &data),
+            &[&[0x00, 0xD6, 0x00, 0x00, 0xFF][..], &data].concat(),
+        );
+
+        // Case 4S
+        assert_encoded(
+            Command::new_with_payload_le(0x00, 0xA4, 0x04, 0x00, 0x100, &[0x01, 0x02]),
+            &[0x00, 0xA4, 0x04, 0x00, 0x02, 0x01, 0x02, 0x00],
+        );
+    }
+
+    #[test]
+    fn empty_payload_omits_lc() {
+        assert_encoded(
+            Command::new_with_payload(0x00, 0xA4, 0x04, 0x00, &[]),
+            &[0x00, 0xA4, 0x04, 0x00],
+        );
+        assert_encoded(
+            Command::new_with_payload_le(0x00, 0xA4, 0x04, 0x00, 0x10, &[]),
+            &[0x00, 0xA4, 0x04, 0x00, 0x10],
+        );
+    }
+
+    /// Examples of each case from ISO/IEC 7816-3, 12.1.3 (extended length fields).
+    #[test]
+    fn extended_cases() {
+        let data = [0xAA; 0x100];
+
+        // Case 2E
+        assert_encoded(
+            Command::new_with_le(0x00, 0xB0, 0x00, 0x00, 0x1234),
+            &[0x00, 0xB0, 0x00, 0x00, 0x00, 0x12, 0x34],
+        );
+        assert_encoded(
+            Command::new_with_le(0x00, 0xB0, 0x00, 0x00, 0x101),
+            &[0x00, 0xB0, 0x00, 0x00, 0x00, 0x01, 0x01],
+        );
+
+        // Case 3E
+        assert_encoded(
+            Command::new_with_payload(0x00, 0xD6, 0x00, 0x00, &data),
+            &[&[0x00, 0xD6, 0x00, 0x00, 0x00, 0x01, 0x00][..], &data].concat(),
+        );
+
+        // Case 4E, with extended Lc forcing extended Le (65536)
+        assert_encoded(
+            Command::new_with_payload_le(0x00, 0x2A, 0x9E, 0x9A, 0, &data),
+            &[
+                &[0x00, 0x2A, 0x9E, 0x9A, 0x00, 0x01, 0x00][..],
+                &data,
+                &[0x00, 0x00],
+            ]
+            .concat(),
+        );
+
+        // Case 4E, with extended Le forcing extended Lc
+        assert_encoded(
+            Command::new_with_payload_le(0x00, 0x2A, 0x9E, 0x9A, 0x200, &[0x01, 0x02]),
+            &[
+                0x00, 0x2A, 0x9E, 0x9A, 0x00, 0x00, 0x02, 0x01, 0x02, 0x02, 0x00,
+            ],
+        );
+    }
+
+    #[test]
+    fn forced_encoding() {
+        // Case 2E with Le=65536, which is not expressible automatically
+        assert_encoded(
+            Command::new_with_le(0x00, 0xB0, 0x00, 0x00, 0)
+                .with_encoding(LengthEncoding::ForceExtended),
+            &[0x00, 0xB0, 0x00, 0x00, 0x00, 0x00, 0x00],
+        );
+
+        // Case 4E with short lengths
+        assert_encoded(
+            Command::new_with_payload_le(0x00, 0xA4, 0x04, 0x00, 0x100, &[0x01, 0x02])
+                .with_encoding(LengthEncoding::ForceExtended),
+            &[
+                0x00, 0xA4, 0x04, 0x00, 0x00, 0x00, 0x02, 0x01, 0x02, 0x01, 0x00,
+            ],
+        );
+
+        // Case 1 does not have any length fields to extend
+        assert_encoded(
+            Command::new(0x00, 0xA4, 0x04, 0x00).with_encoding(LengthEncoding::ForceExtended),
+            &[0x00, 0xA4, 0x04, 0x00],
+        );
+
+        assert_encoded(
+            Command::new_with_payload_le(0x00, 0xA4, 0x04, 0x00, 0x100, &[0x01, 0x02])
+                .with_encoding(LengthEncoding::ForceShort),
+            &[0x00, 0xA4, 0x04, 0x00, 0x02, 0x01, 0x02, 0x00],
+        );
+    }
+
+    #[test]
+    fn unencodable_lengths() {
+        let data = [0xAA; 0x10000];
+
+        assert_eq!(
+            Err(EncodeError::PayloadTooLong(0x100)),
+            Command::new_with_payload(0x00, 0xD6, 0x00, 0x00, &data[..0x100])
+                .with_encoding(LengthEncoding::ForceShort)
+                .is_extended(),
+        );
+        assert_eq!(
+            Err(EncodeError::LeTooLong(0x101)),
+            Command::new_with_le(0x00, 0xB0, 0x00, 0x00, 0x101)
+                .with_encoding(LengthEncoding::ForceShort)
+                .is_extended(),
+        );
+        assert_eq!(
+            Err(EncodeError::PayloadTooLong(0x10000)),
+            Command::new_with_payload(0x00, 0xD6, 0x00, 0x00, &data).is_extended(),
+        );
+    }
+
+    fn assert_round_trip(bytes: &[u8], expected: Command) {
+        let command = Command::parse(bytes).unwrap();
+
+        assert_eq!(expected, command);
+        assert_eq!(bytes, command.to_vec().unwrap().as_slice());
+    }
+
+    #[test]
+    fn parse_cases() {
+        let data = [0xAA; 0x100];
+
+        // Case 1
+        assert_round_trip(
+            &[0x00, 0xA4, 0x04, 0x00],
+            Command::new(0x00, 0xA4, 0x04, 0x00),
+        );
+
+        // Case 2S
+        assert_round_trip(
+            &[0x00, 0xB0, 0x00, 0x00, 0x00],
+            Command::new_with_le(0x00, 0xB0, 0x00, 0x00, 0)
+                .with_encoding(LengthEncoding::ForceShort),
+        );
+
+        // Case 3S
+        assert_round_trip(
+            &[0x00, 0xA4, 0x04, 0x00, 0x02, 0x01, 0x02],
+            Command::new_with_payload(0x00, 0xA4, 0x04, 0x00, &[0x01, 0x02])
+                .with_encoding(LengthEncoding::ForceShort),
+        );
+
+        // Case 4S
+        assert_round_trip(
+            &[0x00, 0xA4, 0x04, 0x00, 0x02, 0x01, 0x02, 0x10],
+            Command::new_with_payload_le(0x00, 0xA4, 0x04, 0x00, 0x10, &[0x01, 0x02])
+                .with_encoding(LengthEncoding::ForceShort),
+        );
+
+        // Case 2E
+        assert_round_trip(
+            &[0x00, 0xB0, 0x00, 0x00, 0x00, 0x00, 0x00],
+            Command::new_with_le(0x00, 0xB0, 0x00, 0x00, 0)
+                .with_encoding(LengthEncoding::ForceExtended),
+        );
+
+        // Case 3E
+        assert_round_trip(
+            &[0x00, 0xD6, 0x00, 0x00, 0x00, 0x00, 0x01, 0xAA],
+            Command::new_with_payload(0x00, 0xD6, 0x00, 0x00, &data[..1])
+                .with_encoding(LengthEncoding::ForceExtended),
+        );
+
+        // Case 4E
+        assert_round_trip(
+            &[
+                &[0x00, 0x2A, 0x9E, 0x9A, 0x00, 0x01, 0x00][..],
+                &data,
+                &[0x01, 0x00],
+            ]
+            .concat(),
+            Command::new_with_payload_le(0x00, 0x2A, 0x9E, 0x9A, 0x100, &data)
+                .with_encoding(LengthEncoding::ForceExtended),
+        );
+    }
+
+    #[test]
+    fn parse_malformed() {
+        assert_eq!(
+            Err(ParseError::TooShort(3)),
+            Command::try_from(&[0x00, 0xA4, 0x04][..]),
+        );
+        assert_eq!(
+            Err(ParseError::Malformed(2)),
+            Command::parse(&[0x00, 0xA4, 0x04, 0x00, 0x00, 0x01]),
+        );
+        assert_eq!(
+            Err(ParseError::ZeroLc),
+            Command::parse(&[0x00, 0xA4, 0x04, 0x00, 0x00, 0x00, 0x00, 0x01]),
+        );
+        assert_eq!(
+            Err(ParseError::LengthMismatch { lc: 3, actual: 2 }),
+            Command::parse(&[0x00, 0xA4, 0x04, 0x00, 0x03, 0x01, 0x02]),
+        );
+        assert_eq!(
+            Err(ParseError::LengthMismatch { lc: 1, actual: 4 }),
+            Command::parse(&[0x00, 0xA4, 0x04, 0x00, 0x00, 0x00, 0x01, 0x01, 0x02, 0x03, 0x04]),
+        );
+    }
+
+    #[test]
+    fn cases() {
+        let data = [0xAA; 0x100];
+        let case = |payload: &[u8], ne: Ne| {
+            Command::new_with_payload(0x00, 0xA4, 0x04, 0x00, payload)
+                .with_ne(ne)
+                .case()
+        };
+
+        assert_eq!(Ok(Case::Case1), case(&[], Ne::None));
+        assert_eq!(Ok(Case::Case2Short), case(&[], Ne::Maximum));
+        assert_eq!(Ok(Case::Case3Short), case(&data[..0xFF], Ne::None));
+        assert_eq!(Ok(Case::Case4Short), case(&[0x01], Ne::Exact(0x100)));
+        assert_eq!(Ok(Case::Case2Extended), case(&[], Ne::Exact(0x101)));
+        assert_eq!(Ok(Case::Case3Extended), case(&data, Ne::None));
+        assert_eq!(Ok(Case::Case4Extended), case(&data, Ne::Exact(0x10)));
+    }
+
+    #[test]
+    fn new_with_case() {
+        assert_encoded(
+            Command::new_with_case(
+                Case::Case2Extended,
+                0x00,
+                0xB0,
+                0x00,
+                0x00,
+                None,
+                Ne::Exact(0x10),
+            )
+            .unwrap(),
+            &[0x00, 0xB0, 0x00, 0x00, 0x00, 0x00, 0x10],
+        );
+        assert_encoded(
+            Command::new_with_case(
+                Case::Case4Short,
+                0x00,
+                0xA4,
+                0x04,
+                0x00,
+                Some(&[0x01]),
+                Ne::Maximum,
+            )
+            .unwrap(),
+            &[0x00, 0xA4, 0x04, 0x00, 0x01, 0x01, 0x00],
+        );
+
+        assert_eq!(
+            Err(EncodeError::CaseMismatch {
+                expected: Case::Case3Short,
+                actual: Case::Case1,
+            }),
+            Command::new_with_case(
+                Case::Case3Short,
+                0x00,
+                0xD6,
+                0x00,
+                0x00,
+                Some(&[]),
+                Ne::None
+            ),
+        );
+        assert_eq!(
+            Err(EncodeError::LeTooLong(0x101)),
+            Command::new_with_case(
+                Case::Case2Short,
+                0x00,
+                0xB0,
+                0x00,
+                0x00,
+                None,
+                Ne::Exact(0x101)
+            ),
+        );
+    }
+
+    #[test]
+    fn expected_lengths() {
+        let command = |ne: Ne| Command::new(0x00, 0xB0, 0x00, 0x00).with_ne(ne);
+
+        assert_eq!(Ok(0), command(Ne::None).expected_len());
+        assert_eq!(Ok(0x100), command(Ne::Maximum).expected_len());
+        assert_eq!(
+            Ok(0x10000),
+            command(Ne::Maximum)
+                .with_encoding(LengthEncoding::ForceExtended)
+                .expected_len(),
+        );
+
+        // Ne of 65536 is encoded as 0000 in extended Le
+        assert_encoded(
+            command(Ne::Exact(0x10000)),
+            &[0x00, 0xB0, 0x00, 0x00, 0x00, 0x00, 0x00],
+        );
+        assert_encoded(command(Ne::Exact(0x100)), &[0x00, 0xB0, 0x00, 0x00, 0x00]);
+
+        assert_eq!(
+            Err(EncodeError::NeOutOfRange(0)),
+            command(Ne::Exact(0)).expected_len(),
+        );
+        assert_eq!(
+            Err(EncodeError::NeOutOfRange(0x10001)),
+            command(Ne::Exact(0x10001)).expected_len(),
+        );
+    }
+
+    #[test]
+    fn write_into_buffer() {
+        let command = Command::new_with_payload_le(0x00, 0xA4, 0x04, 0x00, 0x10, &[0x01, 0x02]);
+        let mut buf = [0u8; 10];
+
+        assert_eq!(Ok(8), command.write(&mut buf));
+        assert_eq!(&[0x00, 0xA4, 0x04, 0x00, 0x02, 0x01, 0x02, 0x10], &buf[..8]);
+        assert_eq!(
+            Err(EncodeError::BufferTooShort {
+                required: 8,
+                actual: 7
+            }),
+            command.write(&mut buf[..7]),
+        );
+        assert_eq!(
+            Err(EncodeError::NeOutOfRange(0)),
+            command.with_ne(Ne::Exact(0)).write(&mut buf),
+        );
+    }
+
+    #[test]
+    fn validate() {
+        let sm = Class::new(0, SecureMessaging::HeaderAuthenticated, false).unwrap();
+
+        assert_eq!(
+            Ok(()),
+            Command::new_with_le(0x00, 0xB0, 0x00, 0x00, 0x10).validate()
+        );
+        assert_eq!(
+            Ok(()),
+            Command::new_with_payload(0x00, 0xCB, 0x3F, 0xFF, &[0x5C, 0x01, 0x6E]).validate(),
+        );
+        assert_eq!(
+            Ok(()),
+            Command::new_with_payload(0x00, 0xB0, 0x00, 0x00, &[0x97, 0x01, 0x10, 0x8E, 0x00])
+                .with_class(sm)
+                .validate(),
+        );
+
+        assert_eq!(
+            Err(Violation::InvalidClass(0xFF)),
+            Command::new(0xFF, 0xA4, 0x00, 0x00).validate(),
+        );
+        assert_eq!(
+            Err(Violation::InvalidInstruction(0x61)),
+            Command::new(0x00, 0x61, 0x00, 0x00).validate(),
+        );
+        assert_eq!(
+            Err(Violation::InvalidInstruction(0x90)),
+            Command::new(0x00, 0x90, 0x00, 0x00).validate(),
+        );
+        assert_eq!(
+            Ok(()),
+            Command::new_with_le(0x00, 0xB1, 0x00, 0x00, 0x10).validate(),
+        );
+        assert_eq!(
+            Err(Violation::NotBerTlv(0xB1)),
+            Command::new_with_payload_le(0x00, 0xB1, 0x00, 0x00, 0x10, &[0x00, 0x10]).validate(),
+        );
+        assert_eq!(
+            Err(Violation::NotBerTlv(0xCB)),
+            Command::new_with_payload(0x00, 0xCB, 0x3F, 0xFF, &[0x5C, 0x02, 0x6E]).validate(),
+        );
+        assert_eq!(
+            Err(Violation::Length(EncodeError::LeTooLong(0x1000))),
+            Command::new_with_le(0x00, 0xB0, 0x00, 0x00, 0x1000)
+                .with_encoding(LengthEncoding::ForceShort)
+                .validate(),
+        );
+        assert_eq!(
+            Err(Violation::SecureMessagingConflict(
+                SecureMessaging::HeaderAuthenticated
+            )),
+            Command::new_with_payload(0x00, 0xA4, 0x04, 0x00, &[0x01, 0x02])
+                .with_class(sm)
+                .validate(),
+        );
+
+        let command = Command::new_with_payload(0xFF, 0x6B, 0x00, 0x00, &[0x01; 0x100])
+            .with_encoding(LengthEncoding::ForceShort);
+        assert_eq!(
+            [
+                Violation::InvalidClass(0xFF),
+                Violation::InvalidInstruction(0x6B),
+                Violation::NotBerTlv(0x6B),
+                Violation::Length(EncodeError::PayloadTooLong(0x100)),
+            ],
+            command.violations().collect::<Vec<_>>().as_slice(),
+        );
+    }
 }
@@ -1,6 +1,6 @@
 use core::fmt::{Display, Formatter};
 
-use crate::Response;
+use crate::{Case, HandleError, Response, SecureMessaging, StatusWord};
 
 /// An error that was returned from the card or reader
 #[derive(Debug)]
@@ -8,13 +8,236 @@ pub struct Error<'a> {
     pub response: Response<'a>,
 }
 
+impl<'a> Error<'a> {
+    /// Returns the status word that the card returned.
+    pub fn status_word(&self) -> StatusWord {
+        self.response.trailer
+    }
+}
+
 impl<'a> Display for Error<'a> {
     fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
-        let (sw1, sw2) = self.response.trailer;
+        let sw = self.status_word();
 
-        write!(f, "The APDU reader returned an error ({sw1:#X}, {sw2:#X}).")
+        write!(f, "The APDU reader returned an error ({sw}).")
     }
 }
 
 #[cfg(feature = "std")]
 impl<'a> std::error::Error for Error<'a> {}
+
+/// An error that occurred while encoding an APDU
+#[derive(Debug, PartialEq, Eq)]
+pub enum EncodeError {
+    /// The payload is too long to be expressed in the selected length format.
+    PayloadTooLong(usize),
+
+    /// The expected length is too long to be expressed in the selected length format.
+    LeTooLong(u32),
+
+    /// The expected length is out of range (1 to 65536).
+    NeOutOfRange(u32),
+
+    /// The buffer is too short to write the entire APDU.
+    BufferTooShort { required: usize, actual: usize },
+
+    /// The response payload is longer than the command expects in its Le.
+    ResponseTooLong { ne: usize, len: usize },
+
+    /// The parts of the command do not match with the case.
+    CaseMismatch { expected: Case, actual: Case },
+}
+
+impl Display for EncodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        use EncodeError::*;
+        match self {
+            PayloadTooLong(len) => write!(
+                f,
+                "The payload is too long to be encoded in the length format. ({len} bytes)",
+            ),
+            LeTooLong(le) => write!(
+                f,
+                "The expected length is too long to be encoded in the length format. ({le} bytes)",
+            ),
+            NeOutOfRange(ne) => write!(
+                f,
+                "The expected length is out of range. ({ne} bytes)",
+            ),
+            BufferTooShort { required, actual } => write!(
+                f,
+                "The buffer is too short to write the APDU. (needs {required} bytes, but {actual} bytes)",
+            ),
+            ResponseTooLong { ne, len } => write!(
+                f,
+                "The response payload is longer than expected. (expected up to {ne} bytes, but {len} bytes)",
+            ),
+            CaseMismatch { expected, actual } => write!(
+                f,
+                "The parts of the command do not match with the case. (expected {expected:?}, but {actual:?})",
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EncodeError {}
+
+/// An error that occurred while parsing an APDU command
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The command is shorter than the 4-byte header.
+    TooShort(usize),
+
+    /// Extended Lc is zero, which is not allowed by the spec.
+    ZeroLc,
+
+    /// The length of the data field and Le does not match with Lc.
+    LengthMismatch { lc: usize, actual: usize },
+
+    /// The body following the header does not match any of the cases.
+    Malformed(usize),
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        use ParseError::*;
+        match self {
+            TooShort(len) => write!(
+                f,
+                "The command is too short to contain the header. ({len} bytes)",
+            ),
+            ZeroLc => write!(f, "The extended Lc must not be zero."),
+            LengthMismatch { lc, actual } => write!(
+                f,
+                "The length of the body does not match with Lc. (Lc is {lc}, but {actual} bytes follow)",
+            ),
+            Malformed(len) => write!(
+                f,
+                "The body of the command is malformed. ({len} bytes)",
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+/// An error that occurred while composing a class byte
+#[derive(Debug, PartialEq, Eq)]
+pub enum ClassError {
+    /// The logical channel number is out of range of interindustry classes (0 to 19).
+    ChannelOutOfRange(u8),
+
+    /// The SM indication cannot be expressed in the further interindustry class.
+    SecureMessagingNotSupported(SecureMessaging),
+
+    /// The class is not interindustry, so channels, SM, or chaining are not defined.
+    NotInterindustry(u8),
+
+    /// The class is not proprietary.
+    NotProprietary(u8),
+}
+
+impl Display for ClassError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        use ClassError::*;
+        match self {
+            ChannelOutOfRange(channel) => {
+                write!(f, "The logical channel is out of range. ({channel})",)
+            }
+            SecureMessagingNotSupported(sm) => write!(
+                f,
+                "The SM indication is not supported on the logical channel. ({sm:?})",
+            ),
+            NotInterindustry(cla) => write!(f, "The class is not interindustry. ({cla:#X})"),
+            NotProprietary(cla) => write!(f, "The class is not proprietary. ({cla:#X})"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ClassError {}
+
+/// A violation of ISO/IEC 7816 rules found in a command
+#[derive(Debug, PartialEq, Eq)]
+pub enum Violation {
+    /// The class byte `FF` is reserved for PPS.
+    InvalidClass(u8),
+
+    /// The instruction byte `6X` or `9X` is invalid, as T=0 uses them as procedure bytes.
+    InvalidInstruction(u8),
+
+    /// The instruction is odd, but the data field is not BER-TLV encoded.
+    NotBerTlv(u8),
+
+    /// The data field or the expected length cannot be encoded in the length format.
+    Length(EncodeError),
+
+    /// The class indicates ISO secure messaging, but the data field is not SM data objects.
+    SecureMessagingConflict(SecureMessaging),
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        use Violation::*;
+        match self {
+            InvalidClass(cla) => write!(f, "The class is reserved for PPS. ({cla:#X})"),
+            InvalidInstruction(ins) => write!(
+                f,
+                "The instruction is invalid as it collides with T=0 procedure bytes. ({ins:#X})",
+            ),
+            NotBerTlv(ins) => write!(
+                f,
+                "The instruction is odd, but the data field is not BER-TLV encoded. ({ins:#X})",
+            ),
+            Length(e) => write!(f, "The lengths of the command are out of range. ({e})"),
+            SecureMessagingConflict(sm) => write!(
+                f,
+                "The data field does not consist of SM data objects. ({sm:?})",
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Violation {}
+
+/// An error that occurred while transmitting an APDU through a transport
+#[derive(Debug)]
+pub enum TransmitError {
+    /// The command cannot be encoded.
+    Encode(EncodeError),
+
+    /// The handler failed to handle the command.
+    Handle(HandleError),
+
+    /// The handler returned a response without the trailer, or longer than the buffer.
+    MalformedResponse(usize),
+}
+
+impl Display for TransmitError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        use TransmitError::*;
+        match self {
+            Encode(e) => write!(f, "Failed to encode the command. ({e})"),
+            Handle(e) => write!(f, "Failed to handle the command. ({e})"),
+            MalformedResponse(len) => write!(f, "The response is malformed. ({len} bytes)"),
+        }
+    }
+}
+
+impl From<EncodeError> for TransmitError {
+    fn from(e: EncodeError) -> Self {
+        Self::Encode(e)
+    }
+}
+
+impl From<HandleError> for TransmitError {
+    fn from(e: HandleError) -> Self {
+        Self::Handle(e)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TransmitError {}
@@ -0,0 +1,333 @@
+use crate::command::SHORT_COMMAND_MAX;
+use crate::{
+    Class, Command, HandleError, HandlerInCtx, Layer, LengthEncoding, Ne, Result, SecureMessaging,
+};
+
+/// Instruction byte of `GET RESPONSE` command
+const INS_GET_RESPONSE: u8 = 0xC0;
+
+/// Default maximum length of the data collected with GET RESPONSE
+const DEFAULT_MAX_LEN: usize = 0x10000;
+
+/// Maximum number of GET RESPONSE commands issued for a command
+const MAX_GET_RESPONSES: usize = 0x1000;
+
+/// A layer to collect the response with GET RESPONSE on `61XX`, and to resend the command on `6CXX`
+#[derive(Clone, Copy, Debug)]
+pub struct GetResponseLayer {
+    max_len: usize,
+}
+
+impl GetResponseLayer {
+    /// Creates a layer collecting up to 65536 bytes of data.
+    pub fn new() -> Self {
+        Self {
+            max_len: DEFAULT_MAX_LEN,
+        }
+    }
+
+    /// Sets the maximum length of the data collected in total.
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+}
+
+impl Default for GetResponseLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<H> Layer<H> for GetResponseLayer {
+    type Handler = GetResponse<H>;
+
+    fn layer(&self, inner: H) -> Self::Handler {
+        GetResponse {
+            inner,
+            max_len: self.max_len,
+        }
+    }
+}
+
+/// An handler that handles `61XX` and `6CXX` from T=0 cards.
+/// On `61XX`, it issues GET RESPONSE repeatedly and concatenates the data into the response.
+/// On `6CXX`, it resends the command once with Le corrected to `XX`.
+#[derive(Clone, Copy, Debug)]
+pub struct GetResponse<H> {
+    inner: H,
+    max_len: usize,
+}
+
+impl<H> GetResponse<H> {
+    /// Wraps the handler, collecting up to 65536 bytes of data.
+    pub fn new(inner: H) -> Self {
+        GetResponseLayer::new().layer(inner)
+    }
+
+    /// Sets the maximum length of the data collected in total.
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /// Returns the inner handler.
+    pub fn into_inner(self) -> H {
+        self.inner
+    }
+}
+
+impl<H> GetResponse<H> {
+    /// Handles the command, resending it with the corrected Le on `6CXX`.
+    fn handle_corrected<Ctx>(&self, ctx: Ctx, command: &[u8], response: &mut [u8]) -> Result
+    where
+        Ctx: Clone,
+        H: HandlerInCtx<Ctx>,
+    {
+        let len = self.inner.handle_in_ctx(ctx.clone(), command, response)?;
+        let le = match trailer(&response[..len]) {
+            Some((0x6C, le)) => le,
+            _ => return Ok(len),
+        };
+
+        // Only short commands can be corrected, as 6CXX can express up to 256 bytes.
+        let corrected = match Command::parse(command) {
+            Ok(command) => command
+                .with_ne(Ne::from(le as u16))
+                .with_encoding(LengthEncoding::ForceShort)
+                .to_array::<SHORT_COMMAND_MAX>(),
+            _ => return Ok(len),
+        };
+
+        match corrected {
+            Ok(corrected) => self.inner.handle_in_ctx(ctx, &corrected, response),
+            _ => Ok(len),
+        }
+    }
+}
+
+impl<Ctx: Clone, H: HandlerInCtx<Ctx>> HandlerInCtx<Ctx> for GetResponse<H> {
+    fn handle_in_ctx(&self, ctx: Ctx, command: &[u8], response: &mut [u8]) -> Result {
+        let mut len = self.handle_corrected(ctx.clone(), command, response)?;
+        let mut offset = 0;
+        let mut count = 0;
+
+        // GET RESPONSE is sent on the same logical channel as the command,
+        // in the interindustry class without secure messaging.
+        let channel = command
+            .first()
+            .and_then(|&cla| Class::from(cla).channel())
+            .unwrap_or_default();
+        let class = Class::new(channel, SecureMessaging::None, false).unwrap_or_default();
+
+        while let Some((0x61, le)) = trailer(&response[offset..offset + len]) {
+            // Stop if the card is not giving any data to GET RESPONSE, to avoid looping forever.
+            if count > 0 && len == 2 {
+                break;
+            }
+
+            count += 1;
+            if count > MAX_GET_RESPONSES {
+                return Err(HandleError::Protocol("too many GET RESPONSE commands"));
+            }
+
+            offset += len - 2;
+
+            let ne = match le {
+                0 => 0x100,
+                le => le as usize,
+            };
+            if offset + ne > self.max_len {
+                return Err(HandleError::ResponseTooLong(self.max_len));
+            }
+
+            let get_response =
+                Command::new_with_le(class.into(), INS_GET_RESPONSE, 0, 0, le as u16)
+                    .to_array::<5>()
+                    .expect("GET RESPONSE always fits in 5 bytes");
+
+            // The buffer required is reported in total, including the data collected so far.
+            len = self
+                .handle_corrected(ctx.clone(), &get_response, &mut response[offset..])
+                .map_err(|e| match e {
+                    HandleError::NotEnoughBuffer(n) => HandleError::NotEnoughBuffer(offset + n),
+                    e => e,
+                })?;
+        }
+
+        Ok(offset + len)
+    }
+}
+
+/// Returns the trailer of the response, or None if the response is too short.
+fn trailer(response: &[u8]) -> Option<(u8, u8)> {
+    match response {
+        [.., sw1, sw2] => Some((*sw1, *sw2)),
+        _ => None,
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use core::cell::Cell;
+
+    use super::*;
+    use crate::mock::MockCard;
+    use crate::{handler_fn, Handler, HandlerBuilder, Recovery};
+
+    /// An in-memory T=0 card that returns the data with GET RESPONSE in chunks.
+    fn card(data: &[u8], chunk: usize) -> MockCard<impl Fn(&[u8]) -> Vec<u8>> {
+        let data = data.to_vec();
+        let offset = Cell::new(0);
+
+        MockCard::new(move |command| {
+            let command = Command::parse(command).unwrap();
+            let remaining = |offset: usize| data.len() - offset;
+            let available = remaining(offset.get()).min(chunk);
+            match (command.ins, command.ne) {
+                // READ BINARY with wrong Le
+                (0xB0, Ne::Exact(n)) if n as usize != data.len() => vec![0x6C, data.len() as u8],
+                (0xB0, _) => [&data[..], &[0x90, 0x00]].concat(),
+                // Case 4 command under T=0, whose response is retrieved with GET RESPONSE
+                (0xA4, _) => vec![0x61, available as u8],
+                (INS_GET_RESPONSE, Ne::Exact(n)) if n as usize != available => {
+                    vec![0x6C, available as u8]
+                }
+                (INS_GET_RESPONSE, _) => {
+                    let start = offset.get();
+                    offset.set(start + available);
+
+                    let next = remaining(offset.get()).min(chunk);
+                    let trailer = match next {
+                        0 => [0x90, 0x00],
+                        n => [0x61, n as u8],
+                    };
+
+                    [&data[start..start + available], &trailer[..]].concat()
+                }
+                _ => vec![0x6D, 0x00],
+            }
+        })
+    }
+
+    #[test]
+    fn get_response() {
+        let data: Vec<u8> = (0..10).collect();
+        let handler = HandlerBuilder::new()
+            .layer(GetResponseLayer::new())
+            .handler(card(&data, 4));
+
+        let mut buf = [0u8; 32];
+        let command = [0x01, 0xA4, 0x04, 0x00, 0x02, 0x3F, 0x00, 0x00];
+        let len = handler.handle(&command, &mut buf).unwrap();
+
+        assert_eq!([&data[..], &[0x90, 0x00]].concat(), &buf[..len]);
+        assert_eq!(
+            vec![
+                command.to_vec(),
+                vec![0x01, 0xC0, 0x00, 0x00, 0x04],
+                vec![0x01, 0xC0, 0x00, 0x00, 0x04],
+                vec![0x01, 0xC0, 0x00, 0x00, 0x02],
+            ],
+            *handler.into_inner().commands.borrow(),
+        );
+    }
+
+    #[test]
+    fn correct_le() {
+        let handler = GetResponse::new(card(&[0x01, 0x02, 0x03], 4));
+
+        let mut buf = [0u8; 8];
+        let len = handler
+            .handle(&[0x00, 0xB0, 0x00, 0x00, 0x10], &mut buf)
+            .unwrap();
+
+        assert_eq!([0x01, 0x02, 0x03, 0x90, 0x00], buf[..len]);
+        assert_eq!(
+            vec![
+                vec![0x00, 0xB0, 0x00, 0x00, 0x10],
+                vec![0x00, 0xB0, 0x00, 0x00, 0x03],
+            ],
+            *handler.into_inner().commands.borrow(),
+        );
+    }
+
+    #[test]
+    fn get_response_class() {
+        let data: Vec<u8> = (0..4).collect();
+        let handler = GetResponse::new(card(&data, 4));
+
+        let mut buf = [0u8; 8];
+        handler
+            .handle(&[0x80, 0xA4, 0x04, 0x00, 0x02, 0x3F, 0x00], &mut buf)
+            .unwrap();
+        handler
+            .handle(&[0x1D, 0xA4, 0x04, 0x00, 0x02, 0x3F, 0x00], &mut buf)
+            .unwrap();
+
+        // Proprietary classes fall back to the basic channel; SM and chaining bits are cleared.
+        let commands = handler.into_inner().commands.into_inner();
+        assert_eq!(vec![0x00, 0xC0, 0x00, 0x00, 0x04], commands[1]);
+        assert_eq!(vec![0x01, 0xC0, 0x00, 0x00, 0x00], commands[3]);
+    }
+
+    #[test]
+    fn stop_without_data() {
+        let handler = GetResponse::new(handler_fn(|_, response| {
+            response[..2].copy_from_slice(&[0x61, 0x10]);
+            Ok(2)
+        }));
+
+        let mut buf = [0u8; 32];
+        let len = handler
+            .handle(&[0x00, 0xA4, 0x04, 0x00, 0x02, 0x3F, 0x00], &mut buf)
+            .unwrap();
+
+        assert_eq!([0x61, 0x10], buf[..len]);
+    }
+
+    #[test]
+    fn too_many_get_responses() {
+        let handler = GetResponse::new(handler_fn(|_, response| {
+            response[..3].copy_from_slice(&[0x00, 0x61, 0x01]);
+            Ok(3)
+        }));
+
+        let mut buf = vec![0u8; MAX_GET_RESPONSES + 8];
+        let result = handler.handle(&[0x00, 0xA4, 0x04, 0x00, 0x02, 0x3F, 0x00], &mut buf);
+
+        let err = result.unwrap_err();
+        assert!(matches!(err, HandleError::Protocol(_)));
+        assert_eq!(Recovery::Fail, err.recovery());
+        assert!(!err.is_retryable());
+    }
+
+    #[test]
+    fn not_enough_buffer() {
+        let handler = GetResponse::new(handler_fn(|command, response| match command[1] {
+            INS_GET_RESPONSE => Err(HandleError::NotEnoughBuffer(6)),
+            _ => {
+                response[..4].copy_from_slice(&[0x01, 0x02, 0x61, 0x04]);
+                Ok(4)
+            }
+        }));
+
+        let mut buf = [0u8; 4];
+        let result = handler.handle(&[0x00, 0xA4, 0x04, 0x00, 0x02, 0x3F, 0x00], &mut buf);
+
+        assert!(matches!(result, Err(HandleError::NotEnoughBuffer(8))));
+    }
+
+    #[test]
+    fn max_len() {
+        let data: Vec<u8> = (0..10).collect();
+        let handler = GetResponse::new(card(&data, 4)).with_max_len(8);
+
+        let mut buf = [0u8; 32];
+        let result = handler.handle(&[0x00, 0xA4, 0x04, 0x00, 0x02, 0x3F, 0x00], &mut buf);
+
+        assert!(matches!(result, Err(HandleError::ResponseTooLong(8))));
+    }
+}
@@ -0,0 +1,233 @@
+use core::fmt::{Debug, Formatter};
+
+use crate::{HandlerInCtx, Result};
+
+/// A layer to wrap an handler, adding a behaviour such as logging or retries
+pub trait Layer<H> {
+    /// The handler that wraps the inner one.
+    type Handler;
+
+    /// Wraps the inner handler with this layer.
+    fn layer(&self, inner: H) -> Self::Handler;
+}
+
+/// A layer that does nothing, returning the inner handler as is
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Identity;
+
+impl<H> Layer<H> for Identity {
+    type Handler = H;
+
+    fn layer(&self, inner: H) -> Self::Handler {
+        inner
+    }
+}
+
+/// A layer made of two layers, where the outer one wraps the handler wrapped by the inner one
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Stack<Inner, Outer> {
+    inner: Inner,
+    outer: Outer,
+}
+
+impl<Inner, Outer> Stack<Inner, Outer> {
+    /// Stacks the two layers.
+    pub fn new(inner: Inner, outer: Outer) -> Self {
+        Self { inner, outer }
+    }
+}
+
+impl<H, Inner: Layer<H>, Outer: Layer<Inner::Handler>> Layer<H> for Stack<Inner, Outer> {
+    type Handler = Outer::Handler;
+
+    fn layer(&self, inner: H) -> Self::Handler {
+        self.outer.layer(self.inner.layer(inner))
+    }
+}
+
+/// A layer made from the closure, which wraps the inner handler
+#[derive(Clone, Copy)]
+pub struct LayerFn<F>(F);
+
+impl<F> Debug for LayerFn<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("LayerFn").finish_non_exhaustive()
+    }
+}
+
+impl<H, T, F: Fn(H) -> T> Layer<H> for LayerFn<F> {
+    type Handler = T;
+
+    fn layer(&self, inner: H) -> Self::Handler {
+        (self.0)(inner)
+    }
+}
+
+/// Creates a layer from the closure, which wraps the inner handler.
+pub fn layer_fn<F>(f: F) -> LayerFn<F> {
+    LayerFn(f)
+}
+
+/// A builder to stack layers onto an handler.
+/// Layers added first are placed outermost, so they see the commands first and the responses last.
+#[derive(Clone, Debug)]
+pub struct HandlerBuilder<L> {
+    layer: L,
+}
+
+impl HandlerBuilder<Identity> {
+    /// Creates a builder without any layers.
+    pub fn new() -> Self {
+        Self { layer: Identity }
+    }
+}
+
+impl Default for HandlerBuilder<Identity> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<L> HandlerBuilder<L> {
+    /// Adds a layer inside the layers added so far.
+    pub fn layer<T>(self, layer: T) -> HandlerBuilder<Stack<T, L>> {
+        HandlerBuilder {
+            layer: Stack::new(layer, self.layer),
+        }
+    }
+
+    /// Returns the layer stacked so far.
+    pub fn into_inner(self) -> L {
+        self.layer
+    }
+
+    /// Wraps the handler with the layers stacked.
+    pub fn handler<H>(self, handler: H) -> L::Handler
+    where
+        L: Layer<H>,
+    {
+        self.layer.layer(handler)
+    }
+}
+
+/// A layer to inspect the commands and the results of the inner handler, e.g. for logging
+#[derive(Clone, Copy)]
+pub struct InspectLayer<F>(F);
+
+impl<F> InspectLayer<F> {
+    /// Creates a layer that calls the closure with each command and the result of handling it.
+    pub fn new(f: F) -> Self {
+        Self(f)
+    }
+}
+
+impl<F> Debug for InspectLayer<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("InspectLayer").finish_non_exhaustive()
+    }
+}
+
+impl<H, F: Clone> Layer<H> for InspectLayer<F> {
+    type Handler = Inspect<H, F>;
+
+    fn layer(&self, inner: H) -> Self::Handler {
+        Inspect {
+            inner,
+            f: self.0.clone(),
+        }
+    }
+}
+
+/// An handler that calls the closure with each command and the result of the inner handler.
+/// The response is given only when the inner handler succeeds.
+#[derive(Clone, Copy)]
+pub struct Inspect<H, F> {
+    inner: H,
+    f: F,
+}
+
+impl<H: Debug, F> Debug for Inspect<H, F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Inspect")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<Ctx, H, F> HandlerInCtx<Ctx> for Inspect<H, F>
+where
+    H: HandlerInCtx<Ctx>,
+    F: Fn(&[u8], core::result::Result<&[u8], &crate::HandleError>),
+{
+    fn handle_in_ctx(&self, ctx: Ctx, command: &[u8], response: &mut [u8]) -> Result {
+        let result = self.inner.handle_in_ctx(ctx, command, response);
+        match &result {
+            Ok(len) => (self.f)(command, Ok(&response[..*len])),
+            Err(e) => (self.f)(command, Err(e)),
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{handler_fn, Handler};
+
+    /// An handler that appends the byte to the response of the inner handler.
+    #[derive(Debug)]
+    struct Append<H>(H, u8);
+
+    impl<H: Handler> HandlerInCtx for Append<H> {
+        fn handle_in_ctx(&self, _ctx: (), command: &[u8], response: &mut [u8]) -> Result {
+            let len = self.0.handle(command, response)?;
+            response[len] = self.1;
+
+            Ok(len + 1)
+        }
+    }
+
+    #[test]
+    fn stack_layers() {
+        let handler = HandlerBuilder::new()
+            .layer(layer_fn(|inner| Append(inner, 0x01)))
+            .layer(layer_fn(|inner| Append(inner, 0x02)))
+            .handler(handler_fn(|_, _| Ok(0)));
+
+        let mut buf = [0u8; 2];
+        assert_eq!(Ok(2), handler.handle(&[], &mut buf).map_err(|_| ()));
+
+        // The layer added first sees the response last.
+        assert_eq!([0x02, 0x01], buf);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn inspect_layer() {
+        use alloc::vec;
+        use alloc::vec::Vec;
+        use core::cell::RefCell;
+
+        let log = RefCell::new(Vec::new());
+        let handler = HandlerBuilder::new()
+            .layer(InspectLayer::new(
+                |command: &[u8], result: core::result::Result<&[u8], &crate::HandleError>| {
+                    log.borrow_mut()
+                        .push((command.to_vec(), result.map(|r| r.to_vec()).ok()));
+                },
+            ))
+            .handler(handler_fn(|_, response| {
+                response[..2].copy_from_slice(&[0x90, 0x00]);
+                Ok(2)
+            }));
+
+        let mut buf = [0u8; 2];
+        handler.handle(&[0x00, 0xA4, 0x00, 0x00], &mut buf).unwrap();
+
+        assert_eq!(
+            vec![(vec![0x00, 0xA4, 0x00, 0x00], Some(vec![0x90, 0x00]))],
+            *log.borrow(),
+        );
+    }
+}
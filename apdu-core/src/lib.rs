@@ -3,16 +3,54 @@
 #![deny(missing_debug_implementations)]
 #![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "async")]
+mod async_handler;
+#[cfg(feature = "alloc")]
+mod buf;
+mod chaining;
+mod class;
 mod command;
 mod error;
+mod get_response;
+mod layer;
+#[cfg(all(test, feature = "alloc"))]
+mod mock;
 mod response;
+#[cfg(feature = "std")]
+mod shared;
+mod sink;
+mod status;
+mod tlv;
+#[cfg(feature = "alloc")]
+mod transport;
 
+#[cfg(feature = "async")]
+pub use async_handler::*;
+#[cfg(feature = "alloc")]
+pub use buf::*;
+pub use chaining::*;
+pub use class::*;
 pub use command::*;
 pub use error::*;
+pub use get_response::*;
+pub use layer::*;
 pub use response::*;
+#[cfg(feature = "std")]
+pub use shared::*;
+pub use sink::{ArrayBuf, Sink};
+pub use status::*;
+#[cfg(feature = "alloc")]
+pub use tlv::put_tlv;
+pub use tlv::tlv_len;
+#[cfg(feature = "alloc")]
+pub use transport::Transport;
 
 use core::fmt::{Debug, Display, Formatter};
 
+#[non_exhaustive]
 pub enum HandleError {
     /// The buffer is too small to write the response.
     /// Reallocate with the capacity and retry.
@@ -20,38 +58,134 @@ pub enum HandleError {
 
     /// Failed to communicate through physical NFC layer.
     /// Hardware or OS API error?
-    Nfc(Box<dyn Display>),
+    #[cfg(feature = "alloc")]
+    Nfc(alloc::boxed::Box<dyn Display>),
+
+    /// Failed to communicate through the transport, described in a static message.
+    /// Available without allocator, e.g. on microcontrollers.
+    Transport(&'static str),
+
+    /// The response exceeds the maximum length allowed, while collecting with GET RESPONSE.
+    ResponseTooLong(usize),
+
+    /// The card did not respond in time.
+    Timeout,
+
+    /// The card was removed from the reader.
+    CardRemoved,
+
+    /// The card was reset, so the state of the card such as selected files is lost.
+    CardReset,
+
+    /// The command or response violates the transmission protocol, described in a static message.
+    Protocol(&'static str),
+
+    /// The reader is not available, e.g. unplugged or used exclusively by another process.
+    ReaderUnavailable,
+
+    /// Failed to communicate because of the source error.
+    #[cfg(feature = "std")]
+    Source(Box<dyn std::error::Error + Send + Sync>),
+}
+
+/// A way to recover from an error in handling a command
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Recovery {
+    /// Sending the command again may succeed.
+    Retry,
+
+    /// Sending the command again with the response buffer grown to the capacity may succeed.
+    GrowBuffer(usize),
+
+    /// The connection to the card must be established again before sending any commands.
+    Reconnect,
+
+    /// The error is not recoverable, or its cause is unknown.
+    Fail,
 }
 
 impl HandleError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    /// Wraps the error as the source.
+    #[cfg(feature = "std")]
+    pub fn from_source(e: impl std::error::Error + Send + Sync + 'static) -> Self {
+        Self::Source(Box::new(e))
+    }
+
+    /// Determines the way to recover from the error.
+    pub fn recovery(&self) -> Recovery {
+        use HandleError::*;
+        match self {
+            NotEnoughBuffer(len) => Recovery::GrowBuffer(*len),
+            Timeout => Recovery::Retry,
+            CardRemoved | CardReset | ReaderUnavailable => Recovery::Reconnect,
+            Transport(_) | ResponseTooLong(_) | Protocol(_) => Recovery::Fail,
+            #[cfg(feature = "alloc")]
+            Nfc(_) => Recovery::Fail,
+            #[cfg(feature = "std")]
+            Source(_) => Recovery::Fail,
+        }
+    }
+
+    /// Determines whether sending the command again may succeed, as is or with a larger buffer.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self.recovery(), Recovery::Retry | Recovery::GrowBuffer(_))
+    }
+
+    /// Determines whether the connection to the card must be established again.
+    pub fn needs_reconnect(&self) -> bool {
+        self.recovery() == Recovery::Reconnect
+    }
+
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         use HandleError::*;
         match self {
             NotEnoughBuffer(size) => write!(
                 f,
                 "The buffer is too small to write the response. (needs {size} bytes)",
             ),
+            #[cfg(feature = "alloc")]
             Nfc(e) => e.fmt(f),
+            Transport(message) => {
+                write!(f, "Failed to communicate through the transport: {message}")
+            }
+            ResponseTooLong(max) => write!(
+                f,
+                "The response exceeds the maximum length allowed. (up to {max} bytes)",
+            ),
+            Timeout => write!(f, "The card did not respond in time."),
+            CardRemoved => write!(f, "The card was removed from the reader."),
+            CardReset => write!(f, "The card was reset."),
+            Protocol(message) => write!(f, "The transmission protocol was violated: {message}"),
+            ReaderUnavailable => write!(f, "The reader is not available."),
+            #[cfg(feature = "std")]
+            Source(e) => Display::fmt(e, f),
         }
     }
 }
 
 impl Debug for HandleError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         Self::fmt(self, f)
     }
 }
 
 impl Display for HandleError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         Self::fmt(self, f)
     }
 }
 
 #[cfg(feature = "std")]
-impl std::error::Error for HandleError {}
+impl std::error::Error for HandleError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Source(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
 
-pub type Result = std::result::Result<usize, HandleError>;
+pub type Result = core::result::Result<usize, HandleError>;
 
 /// An handler to handle an APDU command and receive a response in a specific context.
 pub trait HandlerInCtx<Ctx = ()> {
@@ -61,7 +195,8 @@ pub trait HandlerInCtx<Ctx = ()> {
     fn handle_in_ctx(&self, ctx: Ctx, command: &[u8], response: &mut [u8]) -> Result;
 }
 
-/// An handler to handle an APDU command and receive a response
+/// An handler to handle an APDU command and receive a response.
+/// Implemented for all [`HandlerInCtx<()>`]s.
 pub trait Handler: HandlerInCtx<()> {
     /// Handles the APDU command.
     /// Implementations must transmit the command to the card through a reader,
@@ -70,3 +205,158 @@ pub trait Handler: HandlerInCtx<()> {
         self.handle_in_ctx((), command, response)
     }
 }
+
+impl<T: HandlerInCtx<()> + ?Sized> Handler for T {}
+
+macro_rules! impl_handler_in_ctx_deref {
+    ($($ty: ty),*) => {
+        $(
+            impl<Ctx, T: HandlerInCtx<Ctx> + ?Sized> HandlerInCtx<Ctx> for $ty {
+                fn handle_in_ctx(&self, ctx: Ctx, command: &[u8], response: &mut [u8]) -> Result {
+                    (**self).handle_in_ctx(ctx, command, response)
+                }
+            }
+        )*
+    };
+}
+
+impl_handler_in_ctx_deref!(&T, &mut T);
+
+#[cfg(feature = "alloc")]
+impl_handler_in_ctx_deref!(alloc::boxed::Box<T>, alloc::rc::Rc<T>, alloc::sync::Arc<T>);
+
+/// An handler that handles APDU commands by calling the closure.
+/// Closures cannot implement [`HandlerInCtx`] directly, as it conflicts with the implementation for references.
+#[derive(Clone, Copy)]
+pub struct FnHandler<F>(pub F);
+
+impl<F> Debug for FnHandler<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("FnHandler").finish_non_exhaustive()
+    }
+}
+
+impl<Ctx, F: Fn(Ctx, &[u8], &mut [u8]) -> Result> HandlerInCtx<Ctx> for FnHandler<F> {
+    fn handle_in_ctx(&self, ctx: Ctx, command: &[u8], response: &mut [u8]) -> Result {
+        (self.0)(ctx, command, response)
+    }
+}
+
+/// Creates an handler from the closure, which receives a command and writes the response.
+pub fn handler_fn<F: Fn(&[u8], &mut [u8]) -> Result>(
+    f: F,
+) -> FnHandler<impl Fn((), &[u8], &mut [u8]) -> Result> {
+    FnHandler(move |_: (), command: &[u8], response: &mut [u8]| f(command, response))
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    struct EchoHandler;
+
+    impl HandlerInCtx<u8> for EchoHandler {
+        fn handle_in_ctx(&self, ctx: u8, command: &[u8], response: &mut [u8]) -> Result {
+            response[..command.len()].copy_from_slice(command);
+            response[command.len()] = ctx;
+
+            Ok(command.len() + 1)
+        }
+    }
+
+    impl HandlerInCtx for EchoHandler {
+        fn handle_in_ctx(&self, _ctx: (), command: &[u8], response: &mut [u8]) -> Result {
+            self.handle_in_ctx(0x90, command, response)
+        }
+    }
+
+    fn handle(handler: impl Handler) -> Vec<u8> {
+        let mut buf = [0u8; 8];
+        let len = handler.handle(&[0x01, 0x02], &mut buf).unwrap();
+
+        buf[..len].to_vec()
+    }
+
+    #[test]
+    fn blanket_handlers() {
+        let mut handler = EchoHandler;
+
+        assert_eq!(vec![0x01, 0x02, 0x90], handle(EchoHandler));
+        assert_eq!(vec![0x01, 0x02, 0x90], handle(&handler));
+        assert_eq!(vec![0x01, 0x02, 0x90], handle(&mut handler));
+        assert_eq!(
+            vec![0x01, 0x02, 0x90],
+            handle(Box::new(EchoHandler) as Box<dyn Handler>),
+        );
+        assert_eq!(
+            vec![0x01, 0x02, 0x90],
+            handle(std::rc::Rc::new(EchoHandler)),
+        );
+        assert_eq!(
+            vec![0x01, 0x02, 0x90],
+            handle(std::sync::Arc::new(EchoHandler)),
+        );
+
+        let mut buf = [0u8; 8];
+        let boxed: Box<dyn HandlerInCtx<u8>> = Box::new(EchoHandler);
+        assert_eq!(
+            Ok(3),
+            boxed
+                .handle_in_ctx(0x61, &[0x01, 0x02], &mut buf)
+                .map_err(|_| ())
+        );
+        assert_eq!([0x01, 0x02, 0x61], buf[..3]);
+    }
+
+    #[test]
+    fn recovery() {
+        use std::error::Error;
+
+        assert_eq!(Recovery::Retry, HandleError::Timeout.recovery());
+        assert_eq!(Recovery::Fail, HandleError::Protocol("parity").recovery());
+        assert_eq!(Recovery::Reconnect, HandleError::CardRemoved.recovery());
+        assert_eq!(Recovery::Reconnect, HandleError::CardReset.recovery());
+        assert_eq!(
+            Recovery::Reconnect,
+            HandleError::ReaderUnavailable.recovery()
+        );
+        assert_eq!(Recovery::Fail, HandleError::Transport("unknown").recovery());
+        assert_eq!(
+            Recovery::GrowBuffer(258),
+            HandleError::NotEnoughBuffer(258).recovery()
+        );
+        assert!(HandleError::NotEnoughBuffer(258).is_retryable());
+        assert_eq!(
+            Recovery::Fail,
+            HandleError::Nfc(Box::new("antenna")).recovery()
+        );
+        assert!(!HandleError::ResponseTooLong(256).needs_reconnect());
+
+        let e = HandleError::from_source(std::io::Error::other("disconnected"));
+        assert_eq!(Recovery::Fail, e.recovery());
+        assert_eq!("disconnected", e.to_string());
+        assert_eq!("disconnected", e.source().unwrap().to_string());
+    }
+
+    #[test]
+    fn closure_handlers() {
+        let handler = handler_fn(|command, response| {
+            response[..command.len()].copy_from_slice(command);
+            Ok(command.len())
+        });
+
+        assert_eq!(vec![0x01, 0x02], handle(&handler));
+        assert_eq!(vec![0x01, 0x02], handle(handler));
+
+        let handler = FnHandler(|ctx: u8, _: &[u8], response: &mut [u8]| {
+            response[0] = ctx;
+            Ok(1)
+        });
+        let mut buf = [0u8; 1];
+        assert_eq!(
+            Ok(1),
+            handler.handle_in_ctx(0x6A, &[], &mut buf).map_err(|_| ())
+        );
+        assert_eq!([0x6A], buf);
+    }
+}
@@ -0,0 +1,70 @@
+//! A mock card shared by the tests of handlers and layers.
+
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+use crate::{HandleError, HandlerInCtx, Result};
+
+/// An in-memory card that replies to each command with the function, recording the commands received.
+/// Responses longer than the buffer are refused with [`HandleError::NotEnoughBuffer`].
+pub(crate) struct MockCard<F> {
+    reply: F,
+    pub(crate) commands: RefCell<Vec<Vec<u8>>>,
+}
+
+impl<F: Fn(&[u8]) -> Vec<u8>> MockCard<F> {
+    /// Creates a card that replies with the function.
+    pub(crate) fn new(reply: F) -> Self {
+        Self {
+            reply,
+            commands: RefCell::new(Vec::new()),
+        }
+    }
+}
+
+impl<F: Fn(&[u8]) -> Vec<u8>> HandlerInCtx for MockCard<F> {
+    fn handle_in_ctx(&self, _ctx: (), command: &[u8], response: &mut [u8]) -> Result {
+        self.commands.borrow_mut().push(command.to_vec());
+
+        let reply = (self.reply)(command);
+        if response.len() < reply.len() {
+            return Err(HandleError::NotEnoughBuffer(reply.len()));
+        }
+
+        response[..reply.len()].copy_from_slice(&reply);
+
+        Ok(reply.len())
+    }
+}
+
+#[cfg(feature = "async")]
+impl<F: Fn(&[u8]) -> Vec<u8>> crate::AsyncHandlerInCtx for MockCard<F> {
+    async fn handle_in_ctx(&self, ctx: (), command: &[u8], response: &mut [u8]) -> Result {
+        YieldOnce(false).await;
+
+        HandlerInCtx::handle_in_ctx(self, ctx, command, response)
+    }
+}
+
+/// A future that is pending once, to make sure the handlers are actually awaited.
+#[cfg(feature = "async")]
+struct YieldOnce(bool);
+
+#[cfg(feature = "async")]
+impl core::future::Future for YieldOnce {
+    type Output = ();
+
+    fn poll(
+        mut self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+    ) -> core::task::Poll<Self::Output> {
+        match self.0 {
+            true => core::task::Poll::Ready(()),
+            _ => {
+                self.0 = true;
+                cx.waker().wake_by_ref();
+                core::task::Poll::Pending
+            }
+        }
+    }
+}
@@ -1,10 +1,10 @@
-use crate::Error;
+use crate::{Command, EncodeError, Error, Sink, StatusClass, StatusWord};
 
 /// An response that was received from the card
 #[derive(Debug, Default)]
 pub struct Response<'a> {
     pub payload: &'a [u8],
-    pub trailer: (u8, u8),
+    pub trailer: StatusWord,
 }
 
 impl<'a> Response<'a> {
@@ -14,8 +14,66 @@ impl<'a> Response<'a> {
     }
 
     /// Determines whether the response indicates success or not.
+    /// Besides `90 00`, `91 00` is taken as success, as cards wrapping proprietary commands
+    /// (e.g. MIFARE DESFire) return it on success, with their native status `00` in SW2.
     pub fn is_ok(&self) -> bool {
-        matches!(self.trailer, (0x90, 0x00) | (0x91, 0x00))
+        matches!(self.trailer, StatusWord::OK | StatusWord::OK_PROPRIETARY)
+    }
+
+    /// Converts the response into the payload, along with the status word if it indicates a warning
+    /// or that more data is still available (`61XX`).
+    /// Fails only if the status word indicates an execution error, a checking error, or anything else.
+    pub fn into_result_with_warning(self) -> Result<(&'a [u8], Option<StatusWord>), Error<'a>> {
+        match self.trailer.class() {
+            _ if self.is_ok() => Ok((self.payload, None)),
+            StatusClass::NormalProcessing | StatusClass::Warning => {
+                Ok((self.payload, Some(self.trailer)))
+            }
+            _ => Err(Error { response: self }),
+        }
+    }
+
+    /// Calculates the length of entire the response, including the trailer.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.payload.len() + 2
+    }
+
+    /// Writes a serialised byte stream onto the mutable buffer,
+    /// returning length of the data written.
+    pub fn write(&self, buf: &mut [u8]) -> Result<usize, EncodeError> {
+        let len = self.len();
+        if buf.len() < len {
+            return Err(EncodeError::BufferTooShort {
+                required: len,
+                actual: buf.len(),
+            });
+        }
+
+        self.write_to(&mut &mut buf[..len])
+    }
+
+    /// Writes a serialised byte stream onto the sink, returning length of the data written.
+    pub fn write_to<S: Sink + ?Sized>(&self, sink: &mut S) -> Result<usize, EncodeError> {
+        sink.write_bytes(self.payload)?;
+        sink.write_bytes(&self.trailer.to_bytes())?;
+
+        Ok(self.len())
+    }
+
+    /// Writes a serialised byte stream as a response to the command onto the mutable buffer,
+    /// returning length of the data written.
+    /// Fails if the payload is longer than the command expects in its Le.
+    pub fn write_for(&self, command: &Command, buf: &mut [u8]) -> Result<usize, EncodeError> {
+        let ne = command.expected_len()?;
+        if self.payload.len() > ne {
+            return Err(EncodeError::ResponseTooLong {
+                ne,
+                len: self.payload.len(),
+            });
+        }
+
+        self.write(buf)
     }
 }
 
@@ -25,7 +83,7 @@ impl<'a> From<&'a [u8]> for Response<'a> {
         if len < 2 {
             return Self {
                 payload: bytes,
-                trailer: (0, 0),
+                trailer: StatusWord::default(),
             };
         }
 
@@ -34,12 +92,24 @@ impl<'a> From<&'a [u8]> for Response<'a> {
 
         Self {
             payload: &bytes[..len - 2],
-            trailer: (sw1, sw2),
+            trailer: StatusWord::new(sw1, sw2),
         }
     }
 }
 
-#[cfg(feature = "std")]
+#[cfg(feature = "alloc")]
+impl<'a> From<Response<'a>> for alloc::vec::Vec<u8> {
+    /// Converts the response into octets.
+    fn from(response: Response) -> Self {
+        let mut buf = alloc::vec::Vec::with_capacity(response.len());
+        response
+            .write_to(&mut buf)
+            .expect("vec has enough space for the response");
+
+        buf
+    }
+}
+
 impl<'a> From<Response<'a>> for Result<&'a [u8], Error<'a>> {
     /// Converts the response to a result of octets.
     fn from(response: Response<'a>) -> Self {
@@ -51,3 +121,93 @@ impl<'a> From<Response<'a>> for Result<&'a [u8], Error<'a>> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn response_to_vec() {
+        use alloc::vec;
+        use alloc::vec::Vec;
+
+        let response = Response {
+            payload: &[0x01, 0x02, 0x03],
+            trailer: StatusWord::OK,
+        };
+
+        assert_eq!(vec![0x01, 0x02, 0x03, 0x90, 0x00], Vec::from(response),);
+    }
+
+    #[test]
+    fn result_with_warning() {
+        let payload = [0x01, 0x02];
+        let response = |trailer| Response {
+            payload: &payload,
+            trailer,
+        };
+
+        assert_eq!(
+            Ok((&payload[..], None)),
+            response(StatusWord::OK)
+                .into_result_with_warning()
+                .map_err(|_| ()),
+        );
+        assert_eq!(
+            Ok((&payload[..], Some(StatusWord::END_OF_FILE))),
+            response(StatusWord::END_OF_FILE)
+                .into_result_with_warning()
+                .map_err(|_| ()),
+        );
+        assert_eq!(
+            Ok((&payload[..], Some(StatusWord(0x6110)))),
+            response(StatusWord(0x6110))
+                .into_result_with_warning()
+                .map_err(|_| ()),
+        );
+        assert_eq!(
+            Err(StatusWord::FILE_NOT_FOUND),
+            response(StatusWord::FILE_NOT_FOUND)
+                .into_result_with_warning()
+                .map_err(|e| e.status_word()),
+        );
+    }
+
+    #[test]
+    fn write_for_command() {
+        let response = Response {
+            payload: &[0x01, 0x02, 0x03],
+            trailer: StatusWord::END_OF_FILE,
+        };
+        let mut buf = [0u8; 8];
+
+        assert_eq!(
+            Ok(5),
+            response.write_for(
+                &Command::new_with_le(0x00, 0xB0, 0x00, 0x00, 0x03),
+                &mut buf
+            ),
+        );
+        assert_eq!(&[0x01, 0x02, 0x03, 0x62, 0x82], &buf[..5]);
+
+        assert_eq!(
+            Err(EncodeError::ResponseTooLong { ne: 2, len: 3 }),
+            response.write_for(
+                &Command::new_with_le(0x00, 0xB0, 0x00, 0x00, 0x02),
+                &mut buf
+            ),
+        );
+        assert_eq!(
+            Err(EncodeError::ResponseTooLong { ne: 0, len: 3 }),
+            response.write_for(&Command::new(0x00, 0xB0, 0x00, 0x00), &mut buf),
+        );
+        assert_eq!(
+            Err(EncodeError::BufferTooShort {
+                required: 5,
+                actual: 4
+            }),
+            response.write(&mut buf[..4]),
+        );
+    }
+}
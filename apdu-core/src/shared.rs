@@ -0,0 +1,175 @@
+use std::sync::{Mutex, MutexGuard, PoisonError, TryLockError};
+
+use crate::{HandlerInCtx, Result};
+
+/// An handler shared among threads, like a reader in a service.
+/// Each command is handled exclusively, and [`SharedHandler::begin_transaction`] can be used
+/// to handle a sequence of commands without interleaving with other threads.
+///
+/// Transactions are not reentrant: handling a command on the `SharedHandler` itself,
+/// or beginning another transaction, while a [`Transaction`] is alive on the same thread
+/// deadlocks. Send the commands through the transaction instead.
+///
+/// If a thread panics during a transaction, the handler is still handed out to the next one,
+/// as it holds no state that the panic could break. The card may however be left in the middle
+/// of the sequence, so the next transaction should not rely on the state left by the previous one,
+/// such as the application selected.
+#[derive(Debug, Default)]
+pub struct SharedHandler<H> {
+    inner: Mutex<H>,
+}
+
+impl<H> SharedHandler<H> {
+    /// Wraps the handler to be shared.
+    pub fn new(handler: H) -> Self {
+        Self {
+            inner: Mutex::new(handler),
+        }
+    }
+
+    /// Begins a transaction, blocking until the other transactions end.
+    /// Commands from the other threads are blocked until the transaction is dropped.
+    /// Deadlocks if a transaction is already alive on the current thread.
+    pub fn begin_transaction(&self) -> Transaction<'_, H> {
+        Transaction {
+            // A panic in another transaction does not break the handler itself.
+            guard: self.inner.lock().unwrap_or_else(PoisonError::into_inner),
+        }
+    }
+
+    /// Begins a transaction if no other transactions are in progress, without blocking.
+    pub fn try_begin_transaction(&self) -> Option<Transaction<'_, H>> {
+        let guard = match self.inner.try_lock() {
+            Ok(guard) => guard,
+            Err(TryLockError::Poisoned(e)) => e.into_inner(),
+            Err(TryLockError::WouldBlock) => return None,
+        };
+
+        Some(Transaction { guard })
+    }
+
+    /// Returns the handler shared.
+    pub fn into_inner(self) -> H {
+        self.inner
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<Ctx, H: HandlerInCtx<Ctx>> HandlerInCtx<Ctx> for SharedHandler<H> {
+    /// Handles the command in a transaction of its own.
+    fn handle_in_ctx(&self, ctx: Ctx, command: &[u8], response: &mut [u8]) -> Result {
+        self.begin_transaction()
+            .handle_in_ctx(ctx, command, response)
+    }
+}
+
+/// An exclusive access to a [`SharedHandler`], which ends when dropped.
+/// Commands handled through the transaction never interleave with the ones from other threads.
+#[derive(Debug)]
+pub struct Transaction<'a, H> {
+    guard: MutexGuard<'a, H>,
+}
+
+impl<'a, H> Transaction<'a, H> {
+    /// Returns the handler shared.
+    pub fn handler(&self) -> &H {
+        &self.guard
+    }
+}
+
+impl<'a, Ctx, H: HandlerInCtx<Ctx>> HandlerInCtx<Ctx> for Transaction<'a, H> {
+    fn handle_in_ctx(&self, ctx: Ctx, command: &[u8], response: &mut [u8]) -> Result {
+        self.guard.handle_in_ctx(ctx, command, response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::Handler;
+
+    /// An handler recording the commands received.
+    #[derive(Default)]
+    struct Recorder(RefCell<Vec<Vec<u8>>>);
+
+    impl HandlerInCtx for Recorder {
+        fn handle_in_ctx(&self, _: (), command: &[u8], response: &mut [u8]) -> Result {
+            self.0.borrow_mut().push(command.to_vec());
+            std::thread::yield_now();
+            response[..2].copy_from_slice(&[0x90, 0x00]);
+
+            Ok(2)
+        }
+    }
+
+    #[test]
+    fn transactions() {
+        let shared = Arc::new(SharedHandler::new(Recorder::default()));
+        let threads: Vec<_> = (0..4u8)
+            .map(|thread| {
+                let shared = shared.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..10 {
+                        let transaction = shared.begin_transaction();
+                        for step in 0..3u8 {
+                            let mut buf = [0u8; 2];
+                            transaction.handle(&[thread, step], &mut buf).unwrap();
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        threads.into_iter().for_each(|t| t.join().unwrap());
+
+        let commands = Arc::try_unwrap(shared)
+            .ok()
+            .unwrap()
+            .into_inner()
+            .0
+            .into_inner();
+        assert_eq!(120, commands.len());
+        for sequence in commands.chunks(3) {
+            let thread = sequence[0][0];
+            assert_eq!(
+                vec![vec![thread, 0], vec![thread, 1], vec![thread, 2]],
+                sequence,
+            );
+        }
+    }
+
+    #[test]
+    fn recover_from_panic() {
+        let shared = Arc::new(SharedHandler::new(Recorder::default()));
+        let panicking = shared.clone();
+        let result = std::thread::spawn(move || {
+            let _transaction = panicking.begin_transaction();
+            panic!("panicked during the transaction");
+        })
+        .join();
+
+        assert!(result.is_err());
+
+        let mut buf = [0u8; 2];
+        assert_eq!(2, shared.handle(&[0x00], &mut buf).unwrap());
+        assert!(shared.try_begin_transaction().is_some());
+    }
+
+    #[test]
+    fn try_begin_transaction() {
+        let shared = SharedHandler::new(Recorder::default());
+        let transaction = shared.begin_transaction();
+
+        assert!(shared.try_begin_transaction().is_none());
+
+        drop(transaction);
+
+        let mut buf = [0u8; 2];
+        assert_eq!(2, shared.handle(&[0x00], &mut buf).unwrap());
+        assert!(shared.try_begin_transaction().is_some());
+    }
+}
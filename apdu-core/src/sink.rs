@@ -0,0 +1,161 @@
+use crate::EncodeError;
+
+/// A sink to write serialised APDUs onto, like [`core::fmt::Write`] but for octets
+pub trait Sink {
+    /// Writes all the bytes onto the sink, or fails if the sink does not have enough space.
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), EncodeError>;
+}
+
+impl Sink for &mut [u8] {
+    /// Writes the bytes onto the head of the slice, advancing it to the rest.
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), EncodeError> {
+        if self.len() < bytes.len() {
+            return Err(EncodeError::BufferTooShort {
+                required: bytes.len(),
+                actual: self.len(),
+            });
+        }
+
+        let (head, tail) = core::mem::take(self).split_at_mut(bytes.len());
+        head.copy_from_slice(bytes);
+        *self = tail;
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Sink for alloc::vec::Vec<u8> {
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), EncodeError> {
+        self.extend_from_slice(bytes);
+
+        Ok(())
+    }
+}
+
+/// A fixed-capacity buffer on the stack to write serialised APDUs onto, without any allocator
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArrayBuf<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> ArrayBuf<N> {
+    /// Creates an empty buffer.
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; N],
+            len: 0,
+        }
+    }
+
+    /// Returns the bytes written so far.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    /// Returns the number of bytes that can still be written.
+    pub const fn remaining(&self) -> usize {
+        N - self.len
+    }
+
+    /// Discards all the bytes written so far.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl<const N: usize> Default for ArrayBuf<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> core::ops::Deref for ArrayBuf<N> {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        self.as_slice()
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for ArrayBuf<N> {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl<const N: usize> Sink for ArrayBuf<N> {
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), EncodeError> {
+        let mut rest = &mut self.buf[self.len..];
+        rest.write_bytes(bytes)?;
+        self.len += bytes.len();
+
+        Ok(())
+    }
+}
+
+/// An adapter to write onto [`bytes::BufMut`] as a sink
+#[cfg(feature = "bytes")]
+pub(crate) struct BufMutSink<'a, B: ?Sized>(pub(crate) &'a mut B);
+
+#[cfg(feature = "bytes")]
+impl<'a, B: bytes::BufMut + ?Sized> Sink for BufMutSink<'a, B> {
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), EncodeError> {
+        if self.0.remaining_mut() < bytes.len() {
+            return Err(EncodeError::BufferTooShort {
+                required: bytes.len(),
+                actual: self.0.remaining_mut(),
+            });
+        }
+
+        self.0.put_slice(bytes);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ArrayBuf, Command, EncodeError, Sink};
+
+    #[test]
+    fn write_into_array_buf() {
+        let command = Command::new_with_payload(0x00, 0xA4, 0x04, 0x00, &[0x01, 0x02]);
+        let mut buf = ArrayBuf::<8>::new();
+
+        assert_eq!(Ok(7), command.write_to(&mut buf));
+        assert_eq!(&[0x00, 0xA4, 0x04, 0x00, 0x02, 0x01, 0x02], &buf[..]);
+        assert_eq!(1, buf.remaining());
+        assert_eq!(
+            Err(EncodeError::BufferTooShort {
+                required: 2,
+                actual: 1,
+            }),
+            buf.write_bytes(&[0x00, 0x00]),
+        );
+        assert_eq!(7, buf.len());
+
+        assert_eq!(
+            Ok(&[0x00, 0xA4, 0x04, 0x00, 0x02, 0x01, 0x02][..]),
+            command.to_array::<16>().as_deref(),
+        );
+        assert_eq!(
+            Err(EncodeError::BufferTooShort {
+                required: 7,
+                actual: 4,
+            }),
+            command.to_array::<4>(),
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "bytes")]
+    fn put_into_buf_mut() {
+        let command = Command::new_with_payload(0x00, 0xA4, 0x04, 0x00, &[0x01, 0x02]);
+        let mut buf = bytes::BytesMut::new();
+
+        assert_eq!(Ok(7), command.put(&mut buf));
+        assert_eq!(&[0x00, 0xA4, 0x04, 0x00, 0x02, 0x01, 0x02], &buf[..]);
+    }
+}
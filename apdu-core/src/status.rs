@@ -0,0 +1,346 @@
+use core::fmt::{Display, Formatter};
+
+/// A status word (SW1-SW2) that was returned from the card at the end of the response
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct StatusWord(pub u16);
+
+impl StatusWord {
+    /// `90 00`: Normal processing, no further qualification.
+    pub const OK: Self = Self(0x9000);
+
+    /// `91 00`: Normal processing in proprietary classes, such as ISO-wrapped MIFARE DESFire commands,
+    /// which return their native status `00` (success) in SW2.
+    pub const OK_PROPRIETARY: Self = Self(0x9100);
+
+    /// `62 00`: Warning, no information given (state of non-volatile memory is unchanged).
+    pub const WARNING_NVM_UNCHANGED: Self = Self(0x6200);
+
+    /// `62 81`: Warning, part of returned data may be corrupted.
+    pub const DATA_CORRUPTED: Self = Self(0x6281);
+
+    /// `62 82`: Warning, end of file or record reached before reading Ne bytes.
+    pub const END_OF_FILE: Self = Self(0x6282);
+
+    /// `62 83`: Warning, selected file deactivated.
+    pub const FILE_DEACTIVATED: Self = Self(0x6283);
+
+    /// `63 00`: Warning, no information given (state of non-volatile memory has changed).
+    pub const WARNING_NVM_CHANGED: Self = Self(0x6300);
+
+    /// `64 00`: Execution error, state of non-volatile memory is unchanged.
+    pub const EXECUTION_ERROR: Self = Self(0x6400);
+
+    /// `65 81`: Execution error, memory failure.
+    pub const MEMORY_FAILURE: Self = Self(0x6581);
+
+    /// `67 00`: Wrong length; no further indication.
+    pub const WRONG_LENGTH: Self = Self(0x6700);
+
+    /// `68 81`: Logical channel not supported.
+    pub const LOGICAL_CHANNEL_NOT_SUPPORTED: Self = Self(0x6881);
+
+    /// `68 82`: Secure messaging not supported.
+    pub const SECURE_MESSAGING_NOT_SUPPORTED: Self = Self(0x6882);
+
+    /// `68 84`: Command chaining not supported.
+    pub const CHAINING_NOT_SUPPORTED: Self = Self(0x6884);
+
+    /// `69 82`: Security status not satisfied.
+    pub const SECURITY_STATUS_NOT_SATISFIED: Self = Self(0x6982);
+
+    /// `69 83`: Authentication method blocked.
+    pub const AUTHENTICATION_METHOD_BLOCKED: Self = Self(0x6983);
+
+    /// `69 85`: Conditions of use not satisfied.
+    pub const CONDITIONS_NOT_SATISFIED: Self = Self(0x6985);
+
+    /// `69 86`: Command not allowed (no current EF).
+    pub const COMMAND_NOT_ALLOWED: Self = Self(0x6986);
+
+    /// `6A 80`: Incorrect parameters in the command data field.
+    pub const WRONG_DATA: Self = Self(0x6A80);
+
+    /// `6A 81`: Function not supported.
+    pub const FUNCTION_NOT_SUPPORTED: Self = Self(0x6A81);
+
+    /// `6A 82`: File or application not found.
+    pub const FILE_NOT_FOUND: Self = Self(0x6A82);
+
+    /// `6A 83`: Record not found.
+    pub const RECORD_NOT_FOUND: Self = Self(0x6A83);
+
+    /// `6A 84`: Not enough memory space in the file.
+    pub const NOT_ENOUGH_MEMORY: Self = Self(0x6A84);
+
+    /// `6A 86`: Incorrect parameters P1-P2.
+    pub const INCORRECT_P1_P2: Self = Self(0x6A86);
+
+    /// `6A 88`: Referenced data or reference data not found.
+    pub const REFERENCED_DATA_NOT_FOUND: Self = Self(0x6A88);
+
+    /// `6B 00`: Wrong parameters P1-P2.
+    pub const WRONG_P1_P2: Self = Self(0x6B00);
+
+    /// `6D 00`: Instruction code not supported or invalid.
+    pub const INS_NOT_SUPPORTED: Self = Self(0x6D00);
+
+    /// `6E 00`: Class not supported.
+    pub const CLA_NOT_SUPPORTED: Self = Self(0x6E00);
+
+    /// `6F 00`: No precise diagnosis.
+    pub const NO_PRECISE_DIAGNOSIS: Self = Self(0x6F00);
+
+    /// Constructs a status word from SW1 and SW2.
+    pub const fn new(sw1: u8, sw2: u8) -> Self {
+        Self(u16::from_be_bytes([sw1, sw2]))
+    }
+
+    /// Returns the first byte of the status word.
+    pub const fn sw1(self) -> u8 {
+        (self.0 >> 8) as u8
+    }
+
+    /// Returns the second byte of the status word.
+    pub const fn sw2(self) -> u8 {
+        self.0 as u8
+    }
+
+    /// Returns the status word as octets (SW1, SW2).
+    pub const fn to_bytes(self) -> [u8; 2] {
+        self.0.to_be_bytes()
+    }
+
+    /// Classifies the status word following ISO/IEC 7816-4, 5.6.
+    pub const fn class(self) -> StatusClass {
+        match (self.sw1(), self.sw2()) {
+            (0x90, 0x00) | (0x61, _) => StatusClass::NormalProcessing,
+            (0x62..=0x63, _) => StatusClass::Warning,
+            (0x64..=0x66, _) => StatusClass::ExecutionError,
+            (0x67..=0x6F, _) => StatusClass::CheckingError,
+            (0x90..=0x9F, _) => StatusClass::Proprietary,
+            _ => StatusClass::Invalid,
+        }
+    }
+}
+
+impl From<u16> for StatusWord {
+    fn from(sw: u16) -> Self {
+        Self(sw)
+    }
+}
+
+impl From<StatusWord> for u16 {
+    fn from(sw: StatusWord) -> Self {
+        sw.0
+    }
+}
+
+impl From<(u8, u8)> for StatusWord {
+    fn from((sw1, sw2): (u8, u8)) -> Self {
+        Self::new(sw1, sw2)
+    }
+}
+
+impl From<StatusWord> for (u8, u8) {
+    fn from(sw: StatusWord) -> Self {
+        (sw.sw1(), sw.sw2())
+    }
+}
+
+impl Display for StatusWord {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:02X} {:02X}", self.sw1(), self.sw2())
+    }
+}
+
+/// A category of status words, defined in ISO/IEC 7816-4, 5.6
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StatusClass {
+    /// `90 00` or `61 XX`: The command was processed successfully.
+    NormalProcessing,
+
+    /// `62 XX` or `63 XX`: The command was processed with a warning.
+    Warning,
+
+    /// `64 XX` to `66 XX`: The command was aborted during execution.
+    ExecutionError,
+
+    /// `67 XX` to `6F XX`: The command was rejected before execution.
+    CheckingError,
+
+    /// `9X XX` other than `90 00`: Proprietary status.
+    Proprietary,
+
+    /// Status words not allowed by the spec, such as `60 XX` or outside of `6X XX` and `9X XX`.
+    Invalid,
+}
+
+/// A pattern to match a byte of status words
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BytePattern {
+    /// Matches the exact value.
+    Exact(u8),
+
+    /// Matches values in the inclusive range.
+    Range(u8, u8),
+
+    /// Matches any values.
+    Any,
+}
+
+impl BytePattern {
+    /// Determines whether the byte matches the pattern.
+    pub const fn matches(self, b: u8) -> bool {
+        match self {
+            Self::Exact(v) => b == v,
+            Self::Range(lo, hi) => lo <= b && b <= hi,
+            Self::Any => true,
+        }
+    }
+
+    /// Returns all values matching the pattern, in ascending order.
+    fn values(self) -> core::ops::RangeInclusive<u8> {
+        match self {
+            Self::Exact(v) => v..=v,
+            Self::Range(lo, hi) => lo..=hi,
+            Self::Any => 0x00..=0xFF,
+        }
+    }
+}
+
+/// A pattern of status words that is mapped to a variant
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StatusPattern {
+    pub sw1: BytePattern,
+    pub sw2: BytePattern,
+    pub variant: &'static str,
+}
+
+impl StatusPattern {
+    /// Determines whether the status word matches the pattern.
+    pub const fn matches(&self, sw: StatusWord) -> bool {
+        self.sw1.matches(sw.sw1()) && self.sw2.matches(sw.sw2())
+    }
+
+    /// Finds the lowest status word that converts into the variant, in order of the patterns.
+    /// Status words matched by earlier patterns of other variants are skipped,
+    /// as well as SW1 not allowed by the spec. Patterns with any SW1 are never used.
+    pub fn find_status_word(patterns: &[Self], variant: &str) -> Option<StatusWord> {
+        let converts = |sw: &StatusWord| {
+            patterns
+                .iter()
+                .find(|p| p.matches(*sw))
+                .is_some_and(|p| p.variant == variant)
+        };
+
+        patterns
+            .iter()
+            .filter(|p| p.variant == variant && p.sw1 != BytePattern::Any)
+            .find_map(|p| {
+                p.sw1
+                    .values()
+                    .filter(|sw1| matches!(sw1, 0x61..=0x6F | 0x90..=0x9F))
+                    .flat_map(|sw1| p.sw2.values().map(move |sw2| StatusWord::new(sw1, sw2)))
+                    .find(converts)
+            })
+    }
+}
+
+/// A type that can be mapped back to status words.
+/// This is implemented by deriving `apdu_derive::Response`.
+pub trait StatusWordMapping {
+    /// All patterns of status words mapped to the variants, in order of matching.
+    const STATUS_PATTERNS: &'static [StatusPattern];
+
+    /// Returns the status word that converts into the value,
+    /// or None if the status word cannot be determined exactly.
+    fn status_word(&self) -> Option<StatusWord>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn conversions() {
+        use alloc::string::ToString;
+
+        let sw = StatusWord::new(0x6A, 0x82);
+
+        assert_eq!(StatusWord::FILE_NOT_FOUND, sw);
+        assert_eq!(0x6A82u16, sw.into());
+        assert_eq!((0x6A, 0x82), sw.into());
+        assert_eq!(sw, StatusWord::from((0x6A, 0x82)));
+        assert_eq!([0x6A, 0x82], sw.to_bytes());
+        assert_eq!("6A 82", sw.to_string());
+    }
+
+    #[test]
+    fn find_status_word() {
+        let patterns = [
+            StatusPattern {
+                sw1: BytePattern::Exact(0x6B),
+                sw2: BytePattern::Exact(0x00),
+                variant: "IncorrectParameters",
+            },
+            StatusPattern {
+                sw1: BytePattern::Exact(0x06),
+                sw2: BytePattern::Any,
+                variant: "ClassNotSupported",
+            },
+            StatusPattern {
+                sw1: BytePattern::Exact(0x6E),
+                sw2: BytePattern::Any,
+                variant: "ClassNotSupported",
+            },
+            StatusPattern {
+                sw1: BytePattern::Exact(0x6B),
+                sw2: BytePattern::Any,
+                variant: "IncorrectReference",
+            },
+            StatusPattern {
+                sw1: BytePattern::Any,
+                sw2: BytePattern::Any,
+                variant: "Unknown",
+            },
+        ];
+
+        let find = |variant| StatusPattern::find_status_word(&patterns, variant);
+        assert_eq!(Some(StatusWord(0x6B00)), find("IncorrectParameters"));
+        assert_eq!(Some(StatusWord(0x6E00)), find("ClassNotSupported"));
+        assert_eq!(Some(StatusWord(0x6B01)), find("IncorrectReference"));
+        assert_eq!(None, find("Unknown"));
+    }
+
+    #[test]
+    fn patterns() {
+        let pattern = StatusPattern {
+            sw1: BytePattern::Exact(0x63),
+            sw2: BytePattern::Range(0xC0, 0xCF),
+            variant: "VerifyFailed",
+        };
+
+        assert!(pattern.matches(StatusWord(0x63C0)));
+        assert!(pattern.matches(StatusWord(0x63CF)));
+        assert!(!pattern.matches(StatusWord(0x6300)));
+        assert!(!pattern.matches(StatusWord(0x62C0)));
+        assert!(BytePattern::Any.matches(0xFF));
+    }
+
+    #[test]
+    fn classification() {
+        use StatusClass::*;
+
+        assert_eq!(NormalProcessing, StatusWord::OK.class());
+        assert_eq!(NormalProcessing, StatusWord(0x6110).class());
+        assert_eq!(Warning, StatusWord::END_OF_FILE.class());
+        assert_eq!(Warning, StatusWord(0x63C2).class());
+        assert_eq!(ExecutionError, StatusWord::MEMORY_FAILURE.class());
+        assert_eq!(CheckingError, StatusWord::FILE_NOT_FOUND.class());
+        assert_eq!(CheckingError, StatusWord::CLA_NOT_SUPPORTED.class());
+        assert_eq!(Proprietary, StatusWord(0x9100).class());
+        assert_eq!(Invalid, StatusWord(0x6000).class());
+        assert_eq!(Invalid, StatusWord(0x0000).class());
+    }
+}
@@ -0,0 +1,152 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// Splits the first BER-TLV data object off the data, returning its tag, value and the rest.
+/// Returns `None` if the data object is malformed or truncated.
+pub(crate) fn split(data: &[u8]) -> Option<(&[u8], &[u8], &[u8])> {
+    let (&first, mut rest) = data.split_first()?;
+    let mut tag_len = 1;
+    if first & 0x1F == 0x1F {
+        // Subsequent bytes of the tag have b8 set, except for the last one.
+        loop {
+            let (&b, tail) = rest.split_first()?;
+            rest = tail;
+            tag_len += 1;
+            if b & 0x80 == 0 {
+                break;
+            }
+        }
+    }
+
+    let (&l, mut rest) = rest.split_first()?;
+    let len = match l {
+        0x00..=0x7F => l as usize,
+        0x81..=0x84 => {
+            let n = (l & 0x0F) as usize;
+            if rest.len() < n {
+                return None;
+            }
+
+            let (octets, tail) = rest.split_at(n);
+            rest = tail;
+            octets
+                .iter()
+                .fold(0usize, |len, &b| (len << 8) | b as usize)
+        }
+        // Indefinite and reserved lengths are not allowed in ISO/IEC 7816.
+        _ => return None,
+    };
+
+    if rest.len() < len {
+        return None;
+    }
+
+    let (value, rest) = rest.split_at(len);
+
+    Some((&data[..tag_len], value, rest))
+}
+
+/// Returns whether the data is a non-empty sequence of well-formed BER-TLV data objects.
+/// The predicate is called with every tag to restrict the tags allowed.
+pub(crate) fn is_valid(mut data: &[u8], mut tag: impl FnMut(&[u8]) -> bool) -> bool {
+    if data.is_empty() {
+        return false;
+    }
+
+    while !data.is_empty() {
+        match split(data) {
+            Some((t, _, rest)) if tag(t) => data = rest,
+            _ => return false,
+        }
+    }
+
+    true
+}
+
+/// Returns the number of bytes following the first one in the length field of the BER-TLV.
+fn len_octets(len: usize) -> usize {
+    match len {
+        0x00..=0x7F => 0,
+        _ => core::mem::size_of::<usize>() - (len.leading_zeros() / 8) as usize,
+    }
+}
+
+/// Returns the length of a BER-TLV data object with a single-byte tag and the value of the length.
+pub fn tlv_len(len: usize) -> usize {
+    2 + len_octets(len) + len
+}
+
+/// Appends a BER-TLV data object with a single-byte tag onto the buffer,
+/// with the length encoded in the minimum number of bytes.
+#[cfg(feature = "alloc")]
+pub fn put_tlv(buf: &mut Vec<u8>, tag: u8, value: &[u8]) {
+    let len = value.len();
+    let octets = len_octets(len);
+
+    buf.push(tag);
+    match octets {
+        0 => buf.push(len as u8),
+        n => {
+            buf.push(0x80 | n as u8);
+            buf.extend_from_slice(&len.to_be_bytes()[core::mem::size_of::<usize>() - n..]);
+        }
+    }
+
+    buf.extend_from_slice(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_objects() {
+        assert_eq!(
+            Some((&[0x54][..], &[0x01, 0x02][..], &[0x53, 0x00][..])),
+            split(&[0x54, 0x02, 0x01, 0x02, 0x53, 0x00]),
+        );
+        assert_eq!(
+            Some((&[0x5F, 0x2D][..], &[0xAA][..], &[][..])),
+            split(&[0x5F, 0x2D, 0x01, 0xAA]),
+        );
+
+        let mut long = [0x00; 0x104];
+        long[..4].copy_from_slice(&[0x53, 0x82, 0x01, 0x00]);
+        assert_eq!(
+            Some((&[0x53][..], &[0x00; 0x100][..], &[][..])),
+            split(&long),
+        );
+
+        assert_eq!(None, split(&[]));
+        assert_eq!(None, split(&[0x5F]));
+        assert_eq!(None, split(&[0x54, 0x03, 0x01]));
+        assert_eq!(None, split(&[0x54, 0x80, 0x00, 0x00]));
+        assert_eq!(None, split(&[0x54, 0x82, 0x01]));
+    }
+
+    #[test]
+    fn valid_objects() {
+        assert!(is_valid(&[0x54, 0x01, 0x00, 0x53, 0x00], |_| true));
+        assert!(!is_valid(&[], |_| true));
+        assert!(!is_valid(&[0x54, 0x01, 0x00, 0x53], |_| true));
+        assert!(!is_valid(&[0x54, 0x01, 0x00], |t| t[0] != 0x54));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn put_objects() {
+        for (len, header) in [
+            (0x7F, &[0x53, 0x7F][..]),
+            (0x80, &[0x53, 0x81, 0x80]),
+            (0x100, &[0x53, 0x82, 0x01, 0x00]),
+        ] {
+            let value = alloc::vec![0xAA; len];
+            let mut buf = Vec::new();
+            put_tlv(&mut buf, 0x53, &value);
+
+            assert_eq!(header, &buf[..header.len()]);
+            assert_eq!(tlv_len(len), buf.len());
+            assert_eq!(Some((&[0x53][..], &value[..], &[][..])), split(&buf));
+        }
+    }
+}
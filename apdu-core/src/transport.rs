@@ -0,0 +1,91 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::{Command, Handler, ResponseBuf, TransmitError};
+
+/// Length of the trailer (SW1-SW2) in the response
+pub(crate) const TRAILER_LEN: usize = 2;
+
+/// A transport to transmit an APDU command and receive a parsed response.
+/// Implemented for all [`Handler`]s, allocating the response buffer from the Ne of the command.
+pub trait Transport {
+    /// Transmits the command, returning the owned response with the trailer split off.
+    /// The command is never resent, so it is safe to transmit non-idempotent commands.
+    fn transmit<'a>(&self, command: impl Into<Command<'a>>) -> Result<ResponseBuf, TransmitError>;
+}
+
+impl<H: Handler + ?Sized> Transport for H {
+    fn transmit<'a>(&self, command: impl Into<Command<'a>>) -> Result<ResponseBuf, TransmitError> {
+        let command = command.into();
+        let bytes = command.to_vec()?;
+
+        let mut buf = vec![0u8; command.expected_len()? + TRAILER_LEN];
+        let len = self.handle(&bytes, &mut buf)?;
+
+        into_response(buf, len)
+    }
+}
+
+/// Parses the response written in the buffer, where the length is returned from the handler.
+pub(crate) fn into_response(mut buf: Vec<u8>, len: usize) -> Result<ResponseBuf, TransmitError> {
+    if !(TRAILER_LEN..=buf.len()).contains(&len) {
+        return Err(TransmitError::MalformedResponse(len));
+    }
+
+    buf.truncate(len);
+
+    Ok(buf.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockCard;
+    use crate::{HandleError, StatusWord};
+
+    #[test]
+    fn transmit() {
+        let handler = MockCard::new(|_| vec![0x01, 0x02, 0x90, 0x00]);
+        let response = handler
+            .transmit(Command::new_with_le(0x00, 0xB0, 0x00, 0x00, 0x02))
+            .unwrap();
+
+        assert_eq!(vec![0x01, 0x02], response.payload);
+        assert_eq!(StatusWord::OK, response.trailer);
+        assert_eq!(
+            vec![vec![0x00, 0xB0, 0x00, 0x00, 0x02]],
+            *handler.commands.borrow(),
+        );
+    }
+
+    #[test]
+    fn transmit_without_resending() {
+        let handler = MockCard::new(|_| vec![0x01, 0x02, 0x03, 0x90, 0x00]);
+        let result = handler.transmit(Command::new_with_le(0x00, 0xB0, 0x00, 0x00, 0x02));
+
+        assert!(matches!(
+            result,
+            Err(TransmitError::Handle(HandleError::NotEnoughBuffer(5)))
+        ));
+        assert_eq!(1, handler.commands.borrow().len());
+    }
+
+    #[test]
+    fn transmit_malformed() {
+        let handler = MockCard::new(|_| vec![0x90]);
+        let result = handler.transmit(Command::new(0x00, 0xA4, 0x00, 0x00));
+
+        assert!(matches!(result, Err(TransmitError::MalformedResponse(1))));
+
+        let result = handler.transmit(
+            Command::new_with_le(0x00, 0xB0, 0x00, 0x00, 0x1000)
+                .with_encoding(crate::LengthEncoding::ForceShort),
+        );
+
+        assert!(matches!(
+            result,
+            Err(TransmitError::Encode(crate::EncodeError::LeTooLong(0x1000)))
+        ));
+        assert_eq!(1, handler.commands.borrow().len());
+    }
+}
@@ -29,10 +29,10 @@
 //!
 //! impl<'a> From<apdu_core::Response<'a>> for Response<'a> {
 //!     fn from(response: apdu_core::Response<'a>) -> Self {
-//!         match response.trailer {
+//!         match (response.trailer.sw1(), response.trailer.sw2()) {
 //!             (0x90, 0x00) => Self::Ok(response.payload),
 //!             (0x60..=0x69, _) => Self::NotOk,
-//!             (_, _) => Self::Unknown(response.trailer.0, response.trailer.1),
+//!             (sw1, sw2) => Self::Unknown(sw1, sw2),
 //!         }
 //!     }
 //! }
@@ -61,18 +61,236 @@
 //!     VerifyFailed(#[sw2] #[mask(0x0F)] u8),
 //!
 //!     #[apdu(_, _)]
+//!     #[error("unknown: {0}")]
+//!     Unknown(#[sw] apdu_core::StatusWord),
+//! }
+//! ```
+//!
+//! The entire response can be injected to delegate the conversion to another type:
+//! ```rust
+//! #[derive(Debug, apdu_derive::Response, thiserror::Error)]
+//! enum Warning {
+//!     #[apdu(0x62, 0x82)]
+//!     #[error("end of file")]
+//!     EndOfFile,
+//!
+//!     #[apdu(_, _)]
+//!     #[error("unknown warning: {0:#X} {1:#X}")]
+//!     Unknown(u8, u8),
+//! }
+//!
+//! #[derive(Debug, apdu_derive::Response, thiserror::Error)]
+//! enum Error {
+//!     #[apdu(0x62..=0x63, _)]
+//!     #[error(transparent)]
+//!     Warning(#[response] Warning),
+//!
+//!     #[apdu(_, _)]
 //!     #[error("unknown: {0:#X} {1:#X}")]
 //!     Unknown(u8, u8),
 //! }
 //! ```
+//!
+//! Deriving also implements `apdu_core::StatusWordMapping` to map the variants back to status words.
+//! Variants without bytes injected are mapped to the lowest status word that converts back into them,
+//! skipping the ones matched by earlier variants.
+//! Variants without SW1 to be determined are mapped to None:
+//! ```rust
+//! use apdu_core::{StatusWord, StatusWordMapping};
+//!
+//! #[derive(apdu_derive::Response)]
+//! enum Response {
+//!     #[apdu(0x63, 0xC0..=0xCF)]
+//!     VerifyFailed(#[sw2] #[mask(0x0F)] u8),
+//!
+//!     #[apdu(0x6B, 0x00)]
+//!     IncorrectParameters,
+//!
+//!     #[apdu(0x6B, _)]
+//!     IncorrectReference,
+//!
+//!     #[apdu(_, _)]
+//!     Unknown,
+//! }
+//!
+//! assert_eq!(Some(StatusWord(0x63C2)), Response::VerifyFailed(2).status_word());
+//! assert_eq!(Some(StatusWord(0x6B00)), Response::IncorrectParameters.status_word());
+//! assert_eq!(Some(StatusWord(0x6B01)), Response::IncorrectReference.status_word());
+//! assert_eq!(None, Response::Unknown.status_word());
+//! assert_eq!(4, Response::STATUS_PATTERNS.len());
+//! ```
 
 extern crate proc_macro;
 
 use proc_macro::TokenStream;
-use quote::{quote, ToTokens};
-use syn::{parse_macro_input, Data, DeriveInput, Fields};
+use proc_macro2::{Delimiter, TokenTree};
+use quote::{format_ident, quote, ToTokens};
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Field, Fields, Type, Variant};
+
+/// A pattern of a byte in the status word, parsed from `#[apdu(...)]`
+enum BytePattern {
+    Exact(proc_macro2::TokenStream),
+    Range(proc_macro2::TokenStream, proc_macro2::TokenStream),
+    Any,
+}
+
+impl BytePattern {
+    fn parse(tokens: &[TokenTree]) -> Option<Self> {
+        let is_punct = |i: usize, c: char| matches!(tokens.get(i), Some(TokenTree::Punct(p)) if p.as_char() == c);
+
+        if tokens.iter().enumerate().any(|(i, _)| is_punct(i, '|')) {
+            // Or-patterns cannot be expressed in a single pattern
+            return None;
+        }
+
+        match tokens {
+            [TokenTree::Ident(i)] if i == "_" => return Some(Self::Any),
+            [TokenTree::Punct(p)] if p.as_char() == '_' => return Some(Self::Any),
+            [] => return None,
+            _ => {}
+        }
+
+        let stream = |t: &[TokenTree]| t.iter().cloned().collect::<proc_macro2::TokenStream>();
+        let range = (0..tokens.len()).find(|&i| is_punct(i, '.') && is_punct(i + 1, '.'));
+
+        Some(match range {
+            Some(i) if is_punct(i + 2, '=') => {
+                let (lo, hi) = (stream(&tokens[..i]), stream(&tokens[i + 3..]));
+
+                Self::Range(lo, hi)
+            }
+            Some(i) => {
+                let (lo, hi) = (stream(&tokens[..i]), stream(&tokens[i + 2..]));
+
+                Self::Range(lo, quote! { (#hi) - 1 })
+            }
+            None => Self::Exact(stream(tokens)),
+        })
+    }
+
+    /// Parses patterns of SW1 and SW2 from tokens of the attribute.
+    fn parse_pair(attr: &Attribute) -> Option<(Self, Self)> {
+        let group = match attr.tokens.clone().into_iter().next() {
+            None => return Some((Self::Any, Self::Any)),
+            Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Parenthesis => g,
+            _ => return None,
+        };
+
+        let tokens = group.stream().into_iter().collect::<Vec<_>>();
+        let mut parts = tokens.split(|t| matches!(t, TokenTree::Punct(p) if p.as_char() == ','));
+
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(sw1), Some(sw2), None) => Some((Self::parse(sw1)?, Self::parse(sw2)?)),
+            (Some(sw1), Some(sw2), Some([])) => Some((Self::parse(sw1)?, Self::parse(sw2)?)),
+            _ => None,
+        }
+    }
 
-#[proc_macro_derive(Response, attributes(apdu, sw1, sw2, payload, mask))]
+    fn to_pattern(&self) -> proc_macro2::TokenStream {
+        match self {
+            Self::Exact(v) => quote! { ::apdu_core::BytePattern::Exact(#v) },
+            Self::Range(lo, hi) => quote! { ::apdu_core::BytePattern::Range(#lo, #hi) },
+            Self::Any => quote! { ::apdu_core::BytePattern::Any },
+        }
+    }
+}
+
+fn has_attr(field: &Field, name: &str) -> bool {
+    field.attrs.iter().any(|a| a.path.is_ident(name))
+}
+
+fn is_u8(field: &Field) -> bool {
+    matches!(&field.ty, Type::Path(p) if p.qself.is_none() && p.path.is_ident("u8"))
+}
+
+/// Builds an expression of a byte in the status word,
+/// from the pattern and the field injected with the byte, if any.
+fn byte_of(
+    pattern: &BytePattern,
+    field: Option<(usize, &Field)>,
+) -> Option<proc_macro2::TokenStream> {
+    if let BytePattern::Exact(v) = pattern {
+        return Some(quote! { #v });
+    }
+
+    let (i, field) = field?;
+    let binding = format_ident!("__f{}", i);
+    let mask = field.attrs.iter().find(|a| a.path.is_ident("mask"));
+
+    match (pattern, mask) {
+        (_, _) if !is_u8(field) => None,
+        (_, None) => Some(quote! { *#binding }),
+        (BytePattern::Range(lo, _), Some(m)) => {
+            let m = &m.tokens;
+
+            Some(quote! { ((#lo) & !#m) | (*#binding & #m) })
+        }
+        (_, Some(m)) => {
+            let m = &m.tokens;
+
+            Some(quote! { *#binding & #m })
+        }
+    }
+}
+
+/// Builds an expression of the status word that converts into the variant.
+/// Variants without bytes injected are looked up in the patterns, if all of them could be parsed.
+fn status_word_of(variant: &Variant, lookup: bool) -> proc_macro2::TokenStream {
+    let fields = match &variant.fields {
+        Fields::Unnamed(f) => f.unnamed.iter().enumerate().collect::<Vec<_>>(),
+        Fields::Unit => vec![],
+        Fields::Named(_) => return quote! { ::std::option::Option::None },
+    };
+
+    let find = |name: &str| fields.iter().copied().find(|(_, f)| has_attr(f, name));
+    if let Some((i, _)) = find("sw") {
+        let binding = format_ident!("__f{}", i);
+
+        return quote! { ::std::option::Option::Some(::apdu_core::StatusWord::from(*#binding)) };
+    }
+
+    if let Some((i, _)) = find("response") {
+        let binding = format_ident!("__f{}", i);
+
+        return quote! { ::apdu_core::StatusWordMapping::status_word(#binding) };
+    }
+
+    // Two fields without attributes are injected with SW1 and SW2
+    let unattributed = fields.len() == 2 && fields.iter().all(|(_, f)| f.attrs.is_empty());
+    let (sw1_field, sw2_field) = match unattributed {
+        true => (Some(fields[0]), Some(fields[1])),
+        _ => (find("sw1"), find("sw2")),
+    };
+
+    // Skip the status words shadowed by the other variants, which do not convert back.
+    if lookup && sw1_field.is_none() && sw2_field.is_none() {
+        let name = variant.ident.to_string();
+
+        return quote! {
+            ::apdu_core::StatusPattern::find_status_word(
+                <Self as ::apdu_core::StatusWordMapping>::STATUS_PATTERNS,
+                #name,
+            )
+        };
+    }
+
+    variant
+        .attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("apdu"))
+        .filter_map(BytePattern::parse_pair)
+        .find_map(|(sw1, sw2)| {
+            let sw1 = byte_of(&sw1, sw1_field)?;
+            let sw2 = byte_of(&sw2, sw2_field)?;
+
+            Some(quote! {
+                ::std::option::Option::Some(::apdu_core::StatusWord::new(#sw1, #sw2))
+            })
+        })
+        .unwrap_or_else(|| quote! { ::std::option::Option::None })
+}
+
+#[proc_macro_derive(Response, attributes(apdu, sw, sw1, sw2, payload, response, mask))]
 pub fn derive_response(input: TokenStream) -> TokenStream {
     let item = parse_macro_input!(input as DeriveInput);
     let output: proc_macro2::TokenStream = match item.data {
@@ -120,12 +338,16 @@ pub fn derive_response(input: TokenStream) -> TokenStream {
                                 quote! {}
                             };
 
-                            if f.attrs.iter().any(|a| a.path.is_ident("sw1")) {
-                                quote! { (response.trailer.0 #mask).into(), }
+                            if f.attrs.iter().any(|a| a.path.is_ident("sw")) {
+                                quote! { response.trailer.into(), }
+                            } else if f.attrs.iter().any(|a| a.path.is_ident("sw1")) {
+                                quote! { (sw1 #mask).into(), }
                             } else if f.attrs.iter().any(|a| a.path.is_ident("sw2")) {
-                                quote! { (response.trailer.1 #mask).into(), }
+                                quote! { (sw2 #mask).into(), }
                             } else if f.attrs.iter().any(|a| a.path.is_ident("payload")) {
                                 quote! { response.payload.into(), }
+                            } else if f.attrs.iter().any(|a| a.path.is_ident("response")) {
+                                quote! { response.into(), }
                             } else {
                                 quote! {}
                             }
@@ -137,7 +359,7 @@ pub fn derive_response(input: TokenStream) -> TokenStream {
                     } else if variant.fields.len() == 1 {
                         quote! { #ty #gen_suffix::#ident(response.payload) }
                     } else if variant.fields.len() == 2 {
-                        quote! { #ty #gen_suffix::#ident(response.trailer.0, response.trailer.1) }
+                        quote! { #ty #gen_suffix::#ident(sw1, sw2) }
                     } else {
                         panic!("unsupported type of fields found")
                     };
@@ -145,10 +367,66 @@ pub fn derive_response(input: TokenStream) -> TokenStream {
                     quote! { #left => #right, }
                 });
 
+            let (impl_gen, ty_gen, where_clause) = gen.split_for_impl();
+
+            let patterns = d.variants.iter().flat_map(|variant| {
+                let name = variant.ident.to_string();
+
+                variant
+                    .attrs
+                    .iter()
+                    .filter(|attr| attr.path.is_ident("apdu"))
+                    .filter_map(BytePattern::parse_pair)
+                    .map(move |(sw1, sw2)| {
+                        let (sw1, sw2) = (sw1.to_pattern(), sw2.to_pattern());
+
+                        quote! {
+                            ::apdu_core::StatusPattern {
+                                sw1: #sw1,
+                                sw2: #sw2,
+                                variant: #name,
+                            },
+                        }
+                    })
+            });
+
+            // Or-patterns are not listed in the patterns, so they cannot be looked up.
+            let lookup = d
+                .variants
+                .iter()
+                .flat_map(|variant| &variant.attrs)
+                .filter(|attr| attr.path.is_ident("apdu"))
+                .all(|attr| BytePattern::parse_pair(attr).is_some());
+
+            let status_words = d.variants.iter().map(|variant| {
+                let ident = &variant.ident;
+                let bindings = (0..variant.fields.len()).map(|i| format_ident!("__f{}", i));
+                let left = match &variant.fields {
+                    Fields::Unnamed(_) => quote! { Self::#ident(#(#bindings),*) },
+                    Fields::Named(_) => quote! { Self::#ident { .. } },
+                    Fields::Unit => quote! { Self::#ident },
+                };
+                let right = status_word_of(variant, lookup);
+
+                quote! { #left => #right, }
+            });
+
             quote! {
+                impl #impl_gen ::apdu_core::StatusWordMapping for #ty #ty_gen #where_clause {
+                    const STATUS_PATTERNS: &'static [::apdu_core::StatusPattern] = &[
+                        #(#patterns)*
+                    ];
+
+                    fn status_word(&self) -> ::std::option::Option<::apdu_core::StatusWord> {
+                        match self {
+                            #(#status_words)*
+                        }
+                    }
+                }
+
                 impl<'a> ::std::convert::From<::apdu_core::Response<'a>> for #ty #gen {
                     fn from(response: ::apdu_core::Response<'a>) -> Self {
-                        let (sw1, sw2) = response.trailer;
+                        let (sw1, sw2): (u8, u8) = response.trailer.into();
 
                         match (sw1, sw2) {
                             #(#arms)*
@@ -0,0 +1,271 @@
+//! Logical channels to keep multiple applications selected on one card at the same time.
+//!
+//! [`ChannelManager`] opens channels with `MANAGE CHANNEL` command, handing out [`Channel`]s.
+//! Each channel is an handler that rewrites the class of the commands to be sent on the channel,
+//! and closes the channel when dropped:
+//! ```rust
+//! # let card = apdu::core::handler_fn(|command, response| {
+//! #     let reply: &[u8] = match (command[1], command[2]) {
+//! #         (0x70, 0x00) => &[0x01, 0x90, 0x00],
+//! #         _ => &[0x90, 0x00],
+//! #     };
+//! #     response[..reply.len()].copy_from_slice(reply);
+//! #     Ok(reply.len())
+//! # });
+//! use apdu::Transport;
+//!
+//! let manager = apdu::channel::ChannelManager::new(card);
+//! let piv = manager.open().unwrap();
+//! let openpgp = manager.open().unwrap();
+//!
+//! piv.transmit(apdu::command::select_file(0x04, 0x00, &[0xA0, 0x00, 0x00, 0x03, 0x08])).unwrap();
+//! openpgp.transmit(apdu::command::select_file(0x04, 0x00, &[0xD2, 0x76, 0x00, 0x01, 0x24, 0x01])).unwrap();
+//! ```
+
+use crate::core::{HandleError, HandlerInCtx, TransmitError, Transport};
+use crate::{Class, Command, Handler};
+
+/// Number of the basic channel, which is always open
+const BASIC_CHANNEL: u8 = 0;
+
+/// Maximum number of a logical channel
+const MAX_CHANNEL: u8 = 19;
+
+/// An error that occurred while opening or closing a channel
+#[derive(Debug, thiserror::Error)]
+pub enum ChannelError {
+    #[error(transparent)]
+    Transmit(#[from] TransmitError),
+
+    /// The card refused to manage the channel.
+    #[error("The card refused to manage the channel: {0}")]
+    Refused(crate::Error),
+
+    /// The card assigned a channel number out of range, or did not assign one.
+    #[error("The card assigned an invalid channel number ({0:?})")]
+    InvalidChannel(Option<u8>),
+}
+
+/// A manager to open logical channels on the card through the handler
+#[derive(Debug)]
+pub struct ChannelManager<H> {
+    handler: H,
+}
+
+impl<H: Handler> ChannelManager<H> {
+    /// Creates a manager on the handler.
+    pub fn new(handler: H) -> Self {
+        Self { handler }
+    }
+
+    /// Returns the basic channel, which is always open and never closed.
+    pub fn basic(&self) -> Channel<'_, H> {
+        Channel {
+            handler: &self.handler,
+            number: BASIC_CHANNEL,
+        }
+    }
+
+    /// Opens a channel, whose number is assigned by the card.
+    pub fn open(&self) -> Result<Channel<'_, H>, ChannelError> {
+        let response = self.manage(crate::command::open_channel())?;
+        let number = match response.payload.as_slice() {
+            &[number] if (1..=MAX_CHANNEL).contains(&number) => number,
+            payload => return Err(ChannelError::InvalidChannel(payload.first().copied())),
+        };
+
+        Ok(Channel {
+            handler: &self.handler,
+            number,
+        })
+    }
+
+    /// Opens the channel with the number.
+    pub fn open_with(&self, number: u8) -> Result<Channel<'_, H>, ChannelError> {
+        if !(1..=MAX_CHANNEL).contains(&number) {
+            return Err(ChannelError::InvalidChannel(Some(number)));
+        }
+
+        self.manage(crate::command::ManageChannelCommand::open_with(number))?;
+
+        Ok(Channel {
+            handler: &self.handler,
+            number,
+        })
+    }
+
+    /// Returns the handler that the channels are opened on.
+    pub fn into_inner(self) -> H {
+        self.handler
+    }
+
+    /// Transmits the `MANAGE CHANNEL` command, checking that the card accepted it.
+    fn manage<'a>(
+        &self,
+        command: impl Into<Command<'a>>,
+    ) -> Result<crate::core::ResponseBuf, ChannelError> {
+        let response = self.handler.transmit(command)?;
+        match response.is_ok() {
+            true => Ok(response),
+            _ => Err(ChannelError::Refused(response.as_response().into())),
+        }
+    }
+}
+
+/// A logical channel, that sends every command on the channel.
+/// The channel is closed when dropped, except for the basic channel.
+#[derive(Debug)]
+pub struct Channel<'a, H: Handler> {
+    handler: &'a H,
+    number: u8,
+}
+
+impl<'a, H: Handler> Channel<'a, H> {
+    /// Returns the number of the channel.
+    pub fn number(&self) -> u8 {
+        self.number
+    }
+
+    /// Closes the channel, reporting the error if the card refused to close it.
+    pub fn close(mut self) -> Result<(), ChannelError> {
+        let number = core::mem::replace(&mut self.number, BASIC_CHANNEL);
+
+        ChannelManager::new(self.handler)
+            .manage(crate::command::close_channel(number))
+            .map(|_| ())
+    }
+}
+
+impl<'a, H: Handler> HandlerInCtx<()> for Channel<'a, H> {
+    fn handle_in_ctx(&self, _ctx: (), command: &[u8], response: &mut [u8]) -> crate::core::Result {
+        let class = match command.first() {
+            Some(&cla) => Class::from(cla),
+            _ => return self.handler.handle(command, response),
+        };
+
+        // Proprietary classes do not have channel bits, so they are sent as is.
+        if !class.is_interindustry() {
+            return self.handler.handle(command, response);
+        }
+
+        let class = class.with_channel(self.number).map_err(|_| {
+            HandleError::Transport("The class cannot be used on the logical channel")
+        })?;
+
+        let mut command = command.to_vec();
+        command[0] = class.into();
+
+        self.handler.handle(&command, response)
+    }
+}
+
+impl<'a, H: Handler> Drop for Channel<'a, H> {
+    fn drop(&mut self) {
+        if self.number != BASIC_CHANNEL {
+            // Errors cannot be reported on drop; use `Channel::close` to handle them.
+            let _ = ChannelManager::new(self.handler)
+                .manage(crate::command::close_channel(self.number));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    /// A card that assigns channels in order, recording the commands received.
+    #[derive(Default)]
+    struct MockCard {
+        open: RefCell<Vec<u8>>,
+        commands: RefCell<Vec<Vec<u8>>>,
+    }
+
+    impl HandlerInCtx for MockCard {
+        fn handle_in_ctx(&self, _: (), command: &[u8], response: &mut [u8]) -> crate::core::Result {
+            self.commands.borrow_mut().push(command.to_vec());
+
+            let mut open = self.open.borrow_mut();
+            let reply = match (command[1], command[2], command[3]) {
+                (0x70, 0x00, 0x00) => {
+                    let number = (1..=3).find(|n| !open.contains(n));
+                    match number {
+                        Some(number) => {
+                            open.push(number);
+                            vec![number, 0x90, 0x00]
+                        }
+                        _ => vec![0x6A, 0x81],
+                    }
+                }
+                (0x70, 0x80, number) => {
+                    open.retain(|&n| n != number);
+                    vec![0x90, 0x00]
+                }
+                _ => vec![0x90, 0x00],
+            };
+
+            response[..reply.len()].copy_from_slice(&reply);
+
+            Ok(reply.len())
+        }
+    }
+
+    #[test]
+    fn channels() {
+        let manager = ChannelManager::new(MockCard::default());
+        let first = manager.open().unwrap();
+        let second = manager.open().unwrap();
+
+        assert_eq!(1, first.number());
+        assert_eq!(2, second.number());
+
+        first
+            .transmit(Command::new(0x00, 0xA4, 0x04, 0x00))
+            .unwrap();
+        second
+            .transmit(Command::new(0x0C, 0xA4, 0x04, 0x00))
+            .unwrap();
+        manager
+            .basic()
+            .transmit(Command::new(0x01, 0xA4, 0x04, 0x00))
+            .unwrap();
+
+        drop(first);
+        second.close().unwrap();
+
+        let card = manager.into_inner();
+        assert!(card.open.borrow().is_empty());
+        assert_eq!(
+            vec![
+                vec![0x00, 0x70, 0x00, 0x00, 0x01],
+                vec![0x00, 0x70, 0x00, 0x00, 0x01],
+                vec![0x01, 0xA4, 0x04, 0x00],
+                vec![0x0E, 0xA4, 0x04, 0x00],
+                vec![0x00, 0xA4, 0x04, 0x00],
+                vec![0x00, 0x70, 0x80, 0x01],
+                vec![0x00, 0x70, 0x80, 0x02],
+            ],
+            *card.commands.borrow(),
+        );
+    }
+
+    #[test]
+    fn refused() {
+        let manager = ChannelManager::new(MockCard::default());
+        let _channels = [
+            manager.open().unwrap(),
+            manager.open().unwrap(),
+            manager.open().unwrap(),
+        ];
+
+        assert!(matches!(
+            manager.open(),
+            Err(ChannelError::Refused(crate::Error::FunctionNotSupported))
+        ));
+        assert!(matches!(
+            manager.open_with(20),
+            Err(ChannelError::InvalidChannel(Some(20)))
+        ));
+    }
+}
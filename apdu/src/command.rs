@@ -1,71 +1,165 @@
 //! High-level API to compose commands easily.
 
+use crate::core::{put_tlv, tlv_len, Class, CommandBuf, EncodeError, Ne};
+
 /// Default CLA (class) value of commands
-const CLA_DEFAULT: u8 = 0x00;
+const CLA_DEFAULT: Class = Class(0x00);
+
+pub(crate) const INS_SELECT_FILE: u8 = 0xA4;
+pub(crate) const INS_READ_BINARY: u8 = 0xB0;
+pub(crate) const INS_VERIFY: u8 = 0x20;
+pub(crate) const INS_READ_BINARY_ODD: u8 = 0xB1;
+pub(crate) const INS_UPDATE_BINARY_ODD: u8 = 0xD7;
+pub(crate) const INS_GET_DATA_ODD: u8 = 0xCB;
+pub(crate) const INS_MANAGE_CHANNEL: u8 = 0x70;
+
+/// P1 of `MANAGE CHANNEL` command to open a channel
+const P1_OPEN_CHANNEL: u8 = 0x00;
+
+/// P1 of `MANAGE CHANNEL` command to close a channel
+const P1_CLOSE_CHANNEL: u8 = 0x80;
+
+/// Tag of the data object that holds an offset
+const TAG_OFFSET: u8 = 0x54;
 
-const INS_SELECT_FILE: u8 = 0xA4;
-const INS_READ_BINARY: u8 = 0xB0;
-const INS_VERIFY: u8 = 0x20;
+/// Tag of the data object that holds discretionary data
+const TAG_DISCRETIONARY_DATA: u8 = 0x53;
+
+/// Tag of the data object that holds a tag list
+const TAG_TAG_LIST: u8 = 0x5C;
+
+/// Maximum length of the data field, in an extended length command
+const DATA_MAX: usize = 0xFFFF;
 
 macro_rules! impl_into_vec {
     ($name: ty) => {
-        impl<'a> From<$name> for Vec<u8> {
-            fn from(cmd: $name) -> Self {
-                crate::Command::from(cmd).into()
+        impl_into_vec!($name, crate::Command);
+    };
+    ($name: ty, $via: ty) => {
+        impl<'a> TryFrom<$name> for Vec<u8> {
+            type Error = EncodeError;
+
+            fn try_from(cmd: $name) -> Result<Self, Self::Error> {
+                <$via>::from(cmd).try_into()
             }
         }
     };
 }
 
+/// Checks that the data field of the length fits in a command.
+fn check_data_len(len: usize) -> Result<(), EncodeError> {
+    match len {
+        0..=DATA_MAX => Ok(()),
+        len => Err(EncodeError::PayloadTooLong(len)),
+    }
+}
+
+/// Appends an offset data object (`54`) onto the buffer, encoded in the minimum number of bytes.
+fn put_offset(buf: &mut Vec<u8>, offset: u32) {
+    let bytes = offset.to_be_bytes();
+    let skip = (offset.leading_zeros() / 8).min(3) as usize;
+
+    put_tlv(buf, TAG_OFFSET, &bytes[skip..]);
+}
+
+/// Composes an owned command from the header and the BER-TLV data field.
+fn command_buf(class: Class, ins: u8, p1: u8, p2: u8, ne: Ne, data: Vec<u8>) -> CommandBuf {
+    crate::Command::new_with_payload(class.into(), ins, p1, p2, &data)
+        .with_ne(ne)
+        .into()
+}
+
 /// `SELECT FILE` (0xA4) command.
 #[derive(Debug)]
 pub struct SelectFileCommand<'a> {
+    class: Class,
     p1: u8,
     p2: u8,
+    ne: Ne,
     payload: &'a [u8],
 }
 
 impl<'a> SelectFileCommand<'a> {
     /// Constructs a `SELECT FILE` command.
     pub fn new(p1: u8, p2: u8, payload: &'a [u8]) -> Self {
-        Self { p1, p2, payload }
+        Self {
+            class: CLA_DEFAULT,
+            p1,
+            p2,
+            ne: Ne::None,
+            payload,
+        }
+    }
+
+    /// Sets the class of the command.
+    pub fn with_class(mut self, class: Class) -> Self {
+        self.class = class;
+        self
+    }
+
+    /// Sets the maximum number of bytes expected in the response, such as FCI.
+    pub fn with_ne(mut self, ne: Ne) -> Self {
+        self.ne = ne;
+        self
     }
 }
 
 impl<'a> From<SelectFileCommand<'a>> for crate::Command<'a> {
     fn from(cmd: SelectFileCommand<'a>) -> Self {
-        match cmd.payload.len() {
-            0 => Self::new(CLA_DEFAULT, INS_SELECT_FILE, cmd.p1, cmd.p2),
-            _ => Self::new_with_payload(CLA_DEFAULT, INS_SELECT_FILE, cmd.p1, cmd.p2, cmd.payload),
-        }
+        let cla = cmd.class.into();
+        let command = match cmd.payload.len() {
+            0 => Self::new(cla, INS_SELECT_FILE, cmd.p1, cmd.p2),
+            _ => Self::new_with_payload(cla, INS_SELECT_FILE, cmd.p1, cmd.p2, cmd.payload),
+        };
+
+        command.with_ne(cmd.ne)
     }
 }
 
 impl_into_vec!(SelectFileCommand<'a>);
 
 /// Constructs a `SELECT FILE` command.
-pub fn select_file(p1: u8, p2: u8, payload: &[u8]) -> SelectFileCommand {
+pub fn select_file(p1: u8, p2: u8, payload: &[u8]) -> SelectFileCommand<'_> {
     SelectFileCommand::new(p1, p2, payload)
 }
 
 /// `READ BINARY` (0xB0) command.
 #[derive(Debug)]
 pub struct ReadBinaryCommand {
+    class: Class,
     p1: u8,
     p2: u8,
-    le: u8,
+    ne: Ne,
 }
 
 impl ReadBinaryCommand {
     /// Constructs a `READ BINARY` command.
+    /// Le is the value of short Le field, where `0` means the maximum; use `with_ne` to expect more.
     pub fn new(p1: u8, p2: u8, le: u8) -> Self {
-        Self { p1, p2, le }
+        Self {
+            class: CLA_DEFAULT,
+            p1,
+            p2,
+            ne: Ne::from(le),
+        }
+    }
+
+    /// Sets the class of the command.
+    pub fn with_class(mut self, class: Class) -> Self {
+        self.class = class;
+        self
+    }
+
+    /// Sets the maximum number of bytes expected in the response.
+    pub fn with_ne(mut self, ne: Ne) -> Self {
+        self.ne = ne;
+        self
     }
 }
 
 impl<'a> From<ReadBinaryCommand> for crate::Command<'a> {
     fn from(cmd: ReadBinaryCommand) -> Self {
-        Self::new_with_le(CLA_DEFAULT, INS_READ_BINARY, cmd.p1, cmd.p2, cmd.le.into())
+        Self::new(cmd.class.into(), INS_READ_BINARY, cmd.p1, cmd.p2).with_ne(cmd.ne)
     }
 }
 
@@ -79,6 +173,7 @@ pub fn read_binary(p1: u8, p2: u8, le: u8) -> ReadBinaryCommand {
 /// `VERIFY` (0x20) command.
 #[derive(Debug)]
 pub struct VerifyCommand<'a> {
+    class: Class,
     p2: u8,
     payload: &'a [u8],
 }
@@ -86,15 +181,26 @@ pub struct VerifyCommand<'a> {
 impl<'a> VerifyCommand<'a> {
     /// Constructs a `VERIFY` command.
     pub fn new(p2: u8, payload: &'a [u8]) -> Self {
-        Self { p2, payload }
+        Self {
+            class: CLA_DEFAULT,
+            p2,
+            payload,
+        }
+    }
+
+    /// Sets the class of the command.
+    pub fn with_class(mut self, class: Class) -> Self {
+        self.class = class;
+        self
     }
 }
 
 impl<'a> From<VerifyCommand<'a>> for crate::Command<'a> {
     fn from(cmd: VerifyCommand<'a>) -> Self {
+        let cla = cmd.class.into();
         match cmd.payload.len() {
-            0 => Self::new(CLA_DEFAULT, INS_VERIFY, 0x00, cmd.p2),
-            _ => Self::new_with_payload(CLA_DEFAULT, INS_VERIFY, 0x00, cmd.p2, cmd.payload),
+            0 => Self::new(cla, INS_VERIFY, 0x00, cmd.p2),
+            _ => Self::new_with_payload(cla, INS_VERIFY, 0x00, cmd.p2, cmd.payload),
         }
     }
 }
@@ -102,6 +208,321 @@ impl<'a> From<VerifyCommand<'a>> for crate::Command<'a> {
 impl_into_vec!(VerifyCommand<'a>);
 
 /// Constructs a `VERIFY` command.
-pub fn verify(p2: u8, payload: &[u8]) -> VerifyCommand {
+pub fn verify(p2: u8, payload: &[u8]) -> VerifyCommand<'_> {
     VerifyCommand::new(p2, payload)
 }
+
+/// `READ BINARY` (0xB1) command with an odd instruction.
+/// The offset is wrapped in DO `54`, so that files larger than 32767 bytes can be read.
+#[derive(Debug)]
+pub struct ReadBinaryOddCommand {
+    class: Class,
+    p1: u8,
+    p2: u8,
+    offset: u32,
+    ne: Ne,
+}
+
+impl ReadBinaryOddCommand {
+    /// Constructs a `READ BINARY` command with an odd instruction.
+    /// P1-P2 identifies the file, where `0000` means the current EF.
+    /// Le is the value of short Le field, where `0` means the maximum; use `with_ne` to expect more.
+    pub fn new(p1: u8, p2: u8, offset: u32, le: u8) -> Self {
+        Self {
+            class: CLA_DEFAULT,
+            p1,
+            p2,
+            offset,
+            ne: Ne::from(le),
+        }
+    }
+
+    /// Sets the class of the command.
+    pub fn with_class(mut self, class: Class) -> Self {
+        self.class = class;
+        self
+    }
+
+    /// Sets the maximum number of bytes expected in the response.
+    pub fn with_ne(mut self, ne: Ne) -> Self {
+        self.ne = ne;
+        self
+    }
+}
+
+impl From<ReadBinaryOddCommand> for CommandBuf {
+    fn from(cmd: ReadBinaryOddCommand) -> Self {
+        let mut data = Vec::new();
+        put_offset(&mut data, cmd.offset);
+
+        command_buf(cmd.class, INS_READ_BINARY_ODD, cmd.p1, cmd.p2, cmd.ne, data)
+    }
+}
+
+impl_into_vec!(ReadBinaryOddCommand, CommandBuf);
+
+/// Constructs a `READ BINARY` command with an odd instruction.
+pub fn read_binary_odd(p1: u8, p2: u8, offset: u32, le: u8) -> ReadBinaryOddCommand {
+    ReadBinaryOddCommand::new(p1, p2, offset, le)
+}
+
+/// `UPDATE BINARY` (0xD7) command with an odd instruction.
+/// The offset and the data are wrapped in DO `54` and `53` respectively.
+#[derive(Debug)]
+pub struct UpdateBinaryOddCommand<'a> {
+    class: Class,
+    p1: u8,
+    p2: u8,
+    offset: u32,
+    payload: &'a [u8],
+}
+
+impl<'a> UpdateBinaryOddCommand<'a> {
+    /// Constructs an `UPDATE BINARY` command with an odd instruction.
+    /// P1-P2 identifies the file, where `0000` means the current EF.
+    /// Fails if the data objects wrapping the payload do not fit in a command.
+    pub fn new(p1: u8, p2: u8, offset: u32, payload: &'a [u8]) -> Result<Self, EncodeError> {
+        // DO `54` of the offset takes up to 6 bytes.
+        check_data_len(tlv_len(4) + tlv_len(payload.len()))?;
+
+        Ok(Self {
+            class: CLA_DEFAULT,
+            p1,
+            p2,
+            offset,
+            payload,
+        })
+    }
+
+    /// Sets the class of the command.
+    pub fn with_class(mut self, class: Class) -> Self {
+        self.class = class;
+        self
+    }
+}
+
+impl<'a> From<UpdateBinaryOddCommand<'a>> for CommandBuf {
+    fn from(cmd: UpdateBinaryOddCommand<'a>) -> Self {
+        let mut data = Vec::with_capacity(cmd.payload.len() + 10);
+        put_offset(&mut data, cmd.offset);
+        put_tlv(&mut data, TAG_DISCRETIONARY_DATA, cmd.payload);
+
+        command_buf(
+            cmd.class,
+            INS_UPDATE_BINARY_ODD,
+            cmd.p1,
+            cmd.p2,
+            Ne::None,
+            data,
+        )
+    }
+}
+
+impl_into_vec!(UpdateBinaryOddCommand<'a>, CommandBuf);
+
+/// Constructs an `UPDATE BINARY` command with an odd instruction.
+/// Fails if the data objects wrapping the payload do not fit in a command.
+pub fn update_binary_odd(
+    p1: u8,
+    p2: u8,
+    offset: u32,
+    payload: &[u8],
+) -> Result<UpdateBinaryOddCommand<'_>, EncodeError> {
+    UpdateBinaryOddCommand::new(p1, p2, offset, payload)
+}
+
+/// `GET DATA` (0xCB) command with an odd instruction.
+/// The tags of the data objects to retrieve are wrapped in DO `5C`.
+#[derive(Debug)]
+pub struct GetDataOddCommand<'a> {
+    class: Class,
+    p1: u8,
+    p2: u8,
+    tags: &'a [u8],
+    ne: Ne,
+}
+
+impl<'a> GetDataOddCommand<'a> {
+    /// Constructs a `GET DATA` command with an odd instruction.
+    /// P1-P2 identifies the file, where `3FFF` means the current DF.
+    /// Le is the value of short Le field, where `0` means the maximum; use `with_ne` to expect more.
+    /// Fails if the data object wrapping the tags does not fit in a command.
+    pub fn new(p1: u8, p2: u8, tags: &'a [u8], le: u8) -> Result<Self, EncodeError> {
+        check_data_len(tlv_len(tags.len()))?;
+
+        Ok(Self {
+            class: CLA_DEFAULT,
+            p1,
+            p2,
+            tags,
+            ne: Ne::from(le),
+        })
+    }
+
+    /// Sets the class of the command.
+    pub fn with_class(mut self, class: Class) -> Self {
+        self.class = class;
+        self
+    }
+
+    /// Sets the maximum number of bytes expected in the response.
+    pub fn with_ne(mut self, ne: Ne) -> Self {
+        self.ne = ne;
+        self
+    }
+}
+
+impl<'a> From<GetDataOddCommand<'a>> for CommandBuf {
+    fn from(cmd: GetDataOddCommand<'a>) -> Self {
+        let mut data = Vec::with_capacity(cmd.tags.len() + 4);
+        put_tlv(&mut data, TAG_TAG_LIST, cmd.tags);
+
+        command_buf(cmd.class, INS_GET_DATA_ODD, cmd.p1, cmd.p2, cmd.ne, data)
+    }
+}
+
+impl_into_vec!(GetDataOddCommand<'a>, CommandBuf);
+
+/// Constructs a `GET DATA` command with an odd instruction.
+/// Fails if the data object wrapping the tags does not fit in a command.
+pub fn get_data_odd(
+    p1: u8,
+    p2: u8,
+    tags: &[u8],
+    le: u8,
+) -> Result<GetDataOddCommand<'_>, EncodeError> {
+    GetDataOddCommand::new(p1, p2, tags, le)
+}
+
+/// `MANAGE CHANNEL` (0x70) command.
+#[derive(Debug)]
+pub struct ManageChannelCommand {
+    class: Class,
+    p1: u8,
+    p2: u8,
+    ne: Ne,
+}
+
+impl ManageChannelCommand {
+    /// Constructs a `MANAGE CHANNEL` command to open a channel, whose number is assigned by the card.
+    pub fn open() -> Self {
+        Self {
+            class: CLA_DEFAULT,
+            p1: P1_OPEN_CHANNEL,
+            p2: 0x00,
+            ne: Ne::Exact(1),
+        }
+    }
+
+    /// Constructs a `MANAGE CHANNEL` command to open the channel with the number.
+    pub fn open_with(channel: u8) -> Self {
+        Self {
+            class: CLA_DEFAULT,
+            p1: P1_OPEN_CHANNEL,
+            p2: channel,
+            ne: Ne::None,
+        }
+    }
+
+    /// Constructs a `MANAGE CHANNEL` command to close the channel with the number.
+    pub fn close(channel: u8) -> Self {
+        Self {
+            class: CLA_DEFAULT,
+            p1: P1_CLOSE_CHANNEL,
+            p2: channel,
+            ne: Ne::None,
+        }
+    }
+
+    /// Sets the class of the command, which determines the channel to issue the command on.
+    pub fn with_class(mut self, class: Class) -> Self {
+        self.class = class;
+        self
+    }
+}
+
+impl<'a> From<ManageChannelCommand> for crate::Command<'a> {
+    fn from(cmd: ManageChannelCommand) -> Self {
+        Self::new(cmd.class.into(), INS_MANAGE_CHANNEL, cmd.p1, cmd.p2).with_ne(cmd.ne)
+    }
+}
+
+impl_into_vec!(ManageChannelCommand);
+
+/// Constructs a `MANAGE CHANNEL` command to open a channel, whose number is assigned by the card.
+pub fn open_channel() -> ManageChannelCommand {
+    ManageChannelCommand::open()
+}
+
+/// Constructs a `MANAGE CHANNEL` command to close the channel with the number.
+pub fn close_channel(channel: u8) -> ManageChannelCommand {
+    ManageChannelCommand::close(channel)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_binary_odd_command() {
+        let bytes: Vec<u8> = read_binary_odd(0x00, 0x00, 0x8000, 0).try_into().unwrap();
+        assert_eq!(
+            vec![0x00, 0xB1, 0x00, 0x00, 0x04, 0x54, 0x02, 0x80, 0x00, 0x00],
+            bytes,
+        );
+
+        let bytes: Vec<u8> = read_binary_odd(0x00, 0x00, 0, 0x10).try_into().unwrap();
+        assert_eq!(
+            vec![0x00, 0xB1, 0x00, 0x00, 0x03, 0x54, 0x01, 0x00, 0x10],
+            bytes
+        );
+    }
+
+    #[test]
+    fn update_binary_odd_command() {
+        let bytes: Vec<u8> = update_binary_odd(0x01, 0x02, 0x012345, &[0xAA, 0xBB])
+            .unwrap()
+            .try_into()
+            .unwrap();
+        assert_eq!(
+            vec![
+                0x00, 0xD7, 0x01, 0x02, 0x09, // Header and Lc
+                0x54, 0x03, 0x01, 0x23, 0x45, // Offset
+                0x53, 0x02, 0xAA, 0xBB, // Discretionary data
+            ],
+            bytes,
+        );
+
+        let payload = [0x00; 0x100];
+        let command = CommandBuf::from(update_binary_odd(0x00, 0x00, 0, &payload).unwrap());
+        assert_eq!(
+            Some(&[0x54, 0x01, 0x00, 0x53, 0x82, 0x01, 0x00][..]),
+            command.payload.as_deref().map(|p| &p[..7]),
+        );
+        assert_eq!(Ok(()), command.as_command().validate());
+
+        let payload = [0x00; DATA_MAX];
+        assert!(matches!(
+            update_binary_odd(0x00, 0x00, 0, &payload),
+            Err(EncodeError::PayloadTooLong(_)),
+        ));
+    }
+
+    #[test]
+    fn get_data_odd_command() {
+        let bytes: Vec<u8> = get_data_odd(0x3F, 0xFF, &[0x5F, 0xC1, 0x02], 0)
+            .unwrap()
+            .try_into()
+            .unwrap();
+        assert_eq!(
+            vec![0x00, 0xCB, 0x3F, 0xFF, 0x05, 0x5C, 0x03, 0x5F, 0xC1, 0x02, 0x00],
+            bytes,
+        );
+
+        let tags = [0x5F; DATA_MAX];
+        assert_eq!(
+            Some(EncodeError::PayloadTooLong(DATA_MAX + 4)),
+            get_data_odd(0x3F, 0xFF, &tags, 0).err(),
+        );
+    }
+}
@@ -1,4 +1,5 @@
 /// An error that was returned from the card or reader.
+/// Warnings (`62 XX` and `63 XX`) are wrapped in [`Error::Warning`].
 #[derive(Debug, apdu_derive::Response, thiserror::Error)]
 pub enum Error {
     #[apdu(0x06, _)]
@@ -10,128 +11,22 @@ pub enum Error {
     #[error("Command successfully executed; {0} bytes of data are still available")]
     ResponseBytesStillAvailable(#[sw2] u8),
 
-    #[apdu(0x62, 0x00)]
+    #[apdu(0x62..=0x63, _)]
+    #[error(transparent)]
+    Warning(#[response] Warning),
+
     #[apdu(0x64, 0x00)]
-    #[error("NVRAM not changed")]
+    #[error("Execution error: NVRAM not changed")]
     NvRamNotChanged,
 
-    #[apdu(0x62, 0x81)]
-    #[error("Part of returned data may be corrupted")]
-    DataCorrupted,
-
-    #[apdu(0x62, 0x82)]
-    #[error("End of file")]
-    EndOfFile,
-
-    #[apdu(0x62, 0x83)]
-    #[error("The file is already invalidated")]
-    FileInvalidated,
-
-    #[apdu(0x62, 0x84)]
-    #[error("The file has an invalid format")]
-    FileInvalid,
-
-    #[apdu(0x62, 0x85)]
-    #[error("No input available from a sensor on the card")]
-    NoInputAvailable,
-
-    #[apdu(0x62, 0xA2)]
-    #[error("Wrong R-MAC found")]
-    WrongRMac,
-
-    #[apdu(0x62, 0xA4)]
-    #[error("The card is locked")]
-    CardLocked,
-
-    #[apdu(0x62, 0xC0..=0xCF)]
-    #[error("Counter with value {0}")]
-    CounterValue(
-        #[sw2]
-        #[mask(0x0F)]
-        u8,
-    ),
-
-    #[apdu(0x62, 0xF1)]
-    #[error("Wrong C-MAC found")]
-    WrongCmac,
-
-    #[apdu(0x62, 0xF3)]
-    #[error("Internal reset occurred")]
-    InternalReset,
-
-    #[apdu(0x62, 0xF5)]
-    #[error("The default agent is locked")]
-    DefaultAgentLocked,
-
-    #[apdu(0x62, 0xF7)]
-    #[error("The cardholder is locked")]
-    CardholderLocked,
-
-    #[apdu(0x62, 0xF8)]
-    #[error("Basement is the current agent")]
-    BasementIsCurrentAgent,
-
-    #[apdu(0x63, 0x00)]
-    #[apdu(0x65, 0x00)]
-    #[error("NVRAM changed")]
-    NvRamChanged,
-
-    #[apdu(0x63, 0x81)]
-    #[error("The file was filled up by the last write: loading or updating it is not allowed")]
-    FileFilledUp,
-
-    #[apdu(0x63, 0x82)]
-    #[error("The card key is not supported")]
-    CardKeyNotSupported,
-
-    #[apdu(0x63, 0x83)]
-    #[error("The reader key is not supported")]
-    ReaderKeyNotSupported,
-
-    #[apdu(0x63, 0x84)]
-    #[error("Plaintext transmission is not supported")]
-    PlaintextTransmissionNotSupported,
-
-    #[apdu(0x63, 0x85)]
-    #[error("Secured transmission is not supported")]
-    SecuredTransmissionNotSupported,
-
-    #[apdu(0x63, 0x86)]
-    #[error("Volatile memory is not available")]
-    VmNotAvailable,
-
-    #[apdu(0x63, 0x87)]
-    #[error("Non-volatile memory is not available")]
-    NvmNotAvailable,
-
-    #[apdu(0x63, 0x88)]
-    #[error("The key number is invalid")]
-    KeyNumberInvalid,
-
-    #[apdu(0x63, 0x89)]
-    #[error("The key length is not correct")]
-    KeyLengthNotCorrect,
-
-    #[apdu(0x63, 0xC0..=0xCF)]
-    #[error("Verify failed: {0} tries left")]
-    VerifyFailed(
-        #[sw2]
-        #[mask(0x0F)]
-        u8,
-    ),
-
-    #[apdu(0x63, 0xF1)]
-    #[error("Received insufficient data")]
-    InsufficientData,
-
-    #[apdu(0x63, 0xF2)]
-    #[error("Received insufficient data: proactive command pending")]
-    InsufficientDataPending,
-
     #[apdu(0x64, 0x01)]
     #[error("Command timed out: immediate response required by the card")]
     CommandTimeout,
 
+    #[apdu(0x65, 0x00)]
+    #[error("Execution error: NVRAM changed")]
+    NvRamChanged,
+
     #[apdu(0x65, 0x01)]
     #[error("Failed to write to memory: maybe a hardware issue?")]
     MemoryWriteFailed,
@@ -538,3 +433,158 @@ pub enum Error {
     #[error("Unknown APDU error ({0:#X}, {1:#X})")]
     Unknown(u8, u8),
 }
+
+/// A warning that was returned from the card, or a notice that more data is available.
+/// The processed payload is not included; see [`Success`] that holds both of them.
+#[derive(Debug, apdu_derive::Response, thiserror::Error)]
+pub enum Warning {
+    #[apdu(0x61, _)]
+    #[error("{0} bytes of data are still available")]
+    ResponseBytesStillAvailable(#[sw2] u8),
+
+    #[apdu(0x62, 0x00)]
+    #[error("NVRAM not changed")]
+    NvRamNotChanged,
+
+    #[apdu(0x62, 0x81)]
+    #[error("Part of returned data may be corrupted")]
+    DataCorrupted,
+
+    #[apdu(0x62, 0x82)]
+    #[error("End of file")]
+    EndOfFile,
+
+    #[apdu(0x62, 0x83)]
+    #[error("The file is already invalidated")]
+    FileInvalidated,
+
+    #[apdu(0x62, 0x84)]
+    #[error("The file has an invalid format")]
+    FileInvalid,
+
+    #[apdu(0x62, 0x85)]
+    #[error("No input available from a sensor on the card")]
+    NoInputAvailable,
+
+    #[apdu(0x62, 0xA2)]
+    #[error("Wrong R-MAC found")]
+    WrongRMac,
+
+    #[apdu(0x62, 0xA4)]
+    #[error("The card is locked")]
+    CardLocked,
+
+    #[apdu(0x62, 0xC0..=0xCF)]
+    #[error("Counter with value {0}")]
+    CounterValue(
+        #[sw2]
+        #[mask(0x0F)]
+        u8,
+    ),
+
+    #[apdu(0x62, 0xF1)]
+    #[error("Wrong C-MAC found")]
+    WrongCmac,
+
+    #[apdu(0x62, 0xF3)]
+    #[error("Internal reset occurred")]
+    InternalReset,
+
+    #[apdu(0x62, 0xF5)]
+    #[error("The default agent is locked")]
+    DefaultAgentLocked,
+
+    #[apdu(0x62, 0xF7)]
+    #[error("The cardholder is locked")]
+    CardholderLocked,
+
+    #[apdu(0x62, 0xF8)]
+    #[error("Basement is the current agent")]
+    BasementIsCurrentAgent,
+
+    #[apdu(0x63, 0x00)]
+    #[error("NVRAM changed")]
+    NvRamChanged,
+
+    #[apdu(0x63, 0x81)]
+    #[error("The file was filled up by the last write: loading or updating it is not allowed")]
+    FileFilledUp,
+
+    #[apdu(0x63, 0x82)]
+    #[error("The card key is not supported")]
+    CardKeyNotSupported,
+
+    #[apdu(0x63, 0x83)]
+    #[error("The reader key is not supported")]
+    ReaderKeyNotSupported,
+
+    #[apdu(0x63, 0x84)]
+    #[error("Plaintext transmission is not supported")]
+    PlaintextTransmissionNotSupported,
+
+    #[apdu(0x63, 0x85)]
+    #[error("Secured transmission is not supported")]
+    SecuredTransmissionNotSupported,
+
+    #[apdu(0x63, 0x86)]
+    #[error("Volatile memory is not available")]
+    VmNotAvailable,
+
+    #[apdu(0x63, 0x87)]
+    #[error("Non-volatile memory is not available")]
+    NvmNotAvailable,
+
+    #[apdu(0x63, 0x88)]
+    #[error("The key number is invalid")]
+    KeyNumberInvalid,
+
+    #[apdu(0x63, 0x89)]
+    #[error("The key length is not correct")]
+    KeyLengthNotCorrect,
+
+    #[apdu(0x63, 0xC0..=0xCF)]
+    #[error("Verify failed: {0} tries left")]
+    VerifyFailed(
+        #[sw2]
+        #[mask(0x0F)]
+        u8,
+    ),
+
+    #[apdu(0x63, 0xF1)]
+    #[error("Received insufficient data")]
+    InsufficientData,
+
+    #[apdu(0x63, 0xF2)]
+    #[error("Received insufficient data: proactive command pending")]
+    InsufficientDataPending,
+
+    #[apdu(_, _)]
+    #[error("Unknown APDU warning ({0:#X}, {1:#X})")]
+    Unknown(u8, u8),
+}
+
+/// A payload that was returned from the card successfully, possibly along with a warning
+#[derive(Debug)]
+pub struct Success<'a> {
+    pub payload: &'a [u8],
+    pub warning: Option<Warning>,
+}
+
+impl<'a> TryFrom<crate::Response<'a>> for Success<'a> {
+    type Error = Error;
+
+    /// Converts the response into the payload and the warning.
+    /// `61XX` is treated as success, with the number of bytes still available as the warning.
+    /// Fails only if the response indicates an execution error, a checking error, or anything else.
+    fn try_from(response: crate::Response<'a>) -> Result<Self, Self::Error> {
+        let (payload, warning) = response.into_result_with_warning()?;
+        let warning = warning.map(|sw| {
+            Warning::from(crate::Response {
+                payload,
+                trailer: sw,
+            })
+        });
+
+        Ok(Self { payload, warning })
+    }
+}
@@ -6,7 +6,7 @@
 //! It is fully cross-platform since this crate contains only type declarations.
 //! ```rust
 //! let command = apdu_core::Command::new_with_payload(0x00, 0xA4, 0x12, 0x34, &[0x56, 0x78]);
-//! let bytes: Vec<u8> = command.into();
+//! let bytes: Vec<u8> = command.try_into().unwrap();
 //!
 //! assert_eq!(
 //!     vec![
@@ -23,7 +23,7 @@
 //! It is cross-platform now, but some os-specific features can be added in the future.
 //! ```rust
 //! let command = apdu::command::select_file(0x12, 0x34, &[0x56, 0x78]);
-//! let bytes: Vec<u8> = command.into();
+//! let bytes: Vec<u8> = command.try_into().unwrap();
 //!
 //! assert_eq!(vec![0x00, 0xA4, 0x12, 0x34, 0x02, 0x56, 0x78], bytes);
 //! ```
@@ -72,6 +72,20 @@
 //! }
 //! ```
 //!
+//! `Handler` is implemented for every `HandlerInCtx<()>`, and also for references and smart pointers to them.
+//! Closures can be used as a handler through `handler_fn`:
+//! ```rust
+//! use apdu_core::Handler;
+//!
+//! let handler = apdu_core::handler_fn(|command, response| {
+//!     response[..2].copy_from_slice(&[0x90, 0x00]);
+//!     Ok(2)
+//! });
+//!
+//! let mut response = [0u8; 2];
+//! assert_eq!(2, handler.handle(&[0x00, 0xA4, 0x04, 0x00], &mut response).unwrap());
+//! ```
+//!
 //! Thanks to this abstraction, application developer can choose how the APDU command is transmitted
 //! to the card independently to their payload. This enables you to implement your libraries
 //! that uses APDU in cross-platform easily!
@@ -82,16 +96,20 @@
 
 #![deny(missing_debug_implementations)]
 
+pub mod channel;
 pub mod command;
 pub mod error;
+pub mod status;
 
 pub use apdu_core as core;
 
 /// Procedural macro to derive APDU response. See [apdu-derive](https://docs.rs/apdu-derive/) for details.
 pub use apdu_derive::Response;
 
-pub use crate::core::{Command, Handler, Response};
-pub use crate::error::Error;
+pub use crate::core::{
+    Class, Command, Handler, Ne, Response, StatusWord, StatusWordMapping, Transport,
+};
+pub use crate::error::{Error, Success, Warning};
 
 #[cfg(test)]
 mod tests {
@@ -110,6 +128,9 @@ mod tests {
         #[apdu(0x60..=0x69, _)]
         NotOk,
 
+        #[apdu(0x91, _)]
+        Proprietary(#[sw] crate::StatusWord),
+
         #[apdu(_, _)]
         Unknown(u8, u8),
     }
@@ -146,6 +167,14 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_status_word() {
+        let bytes: Vec<u8> = vec![0x91, 0xAE];
+        let response = Response::from(bytes.as_slice());
+
+        assert_eq!(Response::Proprietary(crate::StatusWord(0x91AE)), response)
+    }
+
     #[test]
     fn test_inject() {
         let bytes: Vec<u8> = vec![0x63, 0xC7];
@@ -157,4 +186,132 @@ mod tests {
             panic!("Response is not VerifyFailed variant")
         }
     }
+
+    #[test]
+    fn test_success_with_warning() {
+        let bytes: Vec<u8> = vec![0x12, 0x34, 0x62, 0x82];
+        let success = crate::Success::try_from(crate::Response::from(bytes.as_slice())).unwrap();
+
+        assert_eq!(&bytes[..2], success.payload);
+        assert!(matches!(success.warning, Some(crate::Warning::EndOfFile)));
+
+        let bytes: Vec<u8> = vec![0x12, 0x61, 0x10];
+        let success = crate::Success::try_from(crate::Response::from(bytes.as_slice())).unwrap();
+
+        assert_eq!(&bytes[..1], success.payload);
+        assert!(matches!(
+            success.warning,
+            Some(crate::Warning::ResponseBytesStillAvailable(0x10))
+        ));
+    }
+
+    #[test]
+    fn test_success_with_error() {
+        let bytes: Vec<u8> = vec![0x6A, 0x82];
+        let error = crate::Success::try_from(crate::Response::from(bytes.as_slice())).unwrap_err();
+
+        assert!(matches!(error, crate::Error::FileNotFound));
+        assert!(matches!(
+            crate::Error::from(&[0x63, 0xC2][..]),
+            crate::Error::Warning(crate::Warning::VerifyFailed(2)),
+        ));
+    }
+
+    #[test]
+    fn test_reverse_status_word() {
+        use crate::{Error, StatusWord, StatusWordMapping, Warning};
+
+        assert_eq!(Some(StatusWord(0x6A82)), Error::FileNotFound.status_word());
+        assert_eq!(
+            Some(StatusWord(0x6105)),
+            Error::ResponseBytesStillAvailable(5).status_word()
+        );
+        assert_eq!(
+            Some(StatusWord(0x63C2)),
+            Warning::VerifyFailed(2).status_word()
+        );
+        assert_eq!(
+            Some(StatusWord(0x6282)),
+            Error::Warning(Warning::EndOfFile).status_word(),
+        );
+        assert_eq!(
+            Some(StatusWord(0x7000)),
+            Error::Unknown(0x70, 0x00).status_word()
+        );
+        assert_eq!(
+            Some(StatusWord(0x6E00)),
+            Error::ClassNotSupported.status_word()
+        );
+        assert_eq!(Some(StatusWord(0x6700)), Error::WrongLength.status_word());
+        assert_eq!(
+            Some(StatusWord(0x6D00)),
+            Error::InstructionInvalid.status_word()
+        );
+
+        // `6B 00` converts into `IncorrectParameters`, which is matched earlier.
+        assert_eq!(
+            Some(StatusWord(0x6B01)),
+            Error::IncorrectReference.status_word()
+        );
+    }
+
+    #[test]
+    fn test_status_patterns() {
+        use crate::core::{BytePattern, StatusPattern};
+        use crate::{Error, StatusWordMapping};
+
+        assert_eq!(
+            Some(&StatusPattern {
+                sw1: BytePattern::Exact(0x63),
+                sw2: BytePattern::Range(0xC0, 0xCF),
+                variant: "VerifyFailed",
+            }),
+            Response::STATUS_PATTERNS.get(1),
+        );
+
+        // All exact variants must be mapped back to status words
+        for pattern in Error::STATUS_PATTERNS {
+            if let (BytePattern::Exact(sw1), BytePattern::Exact(sw2)) = (pattern.sw1, pattern.sw2) {
+                assert!(Error::from(&[sw1, sw2][..]).status_word().is_some());
+            }
+        }
+
+        // All variants mapped back to status words must be converted back into themselves
+        for sw in 0..=u16::MAX {
+            let error = Error::from(&sw.to_be_bytes()[..]);
+            if let Some(sw) = error.status_word() {
+                assert_eq!(
+                    format!("{error:?}"),
+                    format!("{:?}", Error::from(&sw.to_bytes()[..])),
+                    "{sw}",
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_command_class() {
+        let class = crate::Class::new(5, crate::core::SecureMessaging::None, false).unwrap();
+        let bytes: Vec<u8> = crate::command::read_binary(0x00, 0x00, 0x10)
+            .with_class(class)
+            .try_into()
+            .unwrap();
+
+        assert_eq!(vec![0x41, 0xB0, 0x00, 0x00, 0x10], bytes);
+    }
+
+    #[test]
+    fn test_command_ne() {
+        let bytes: Vec<u8> = crate::command::read_binary(0x00, 0x00, 0x00)
+            .with_ne(crate::Ne::Exact(0x1000))
+            .try_into()
+            .unwrap();
+        assert_eq!(vec![0x00, 0xB0, 0x00, 0x00, 0x00, 0x10, 0x00], bytes);
+
+        let bytes: Vec<u8> = crate::command::select_file(0x04, 0x00, &[0x01])
+            .with_ne(crate::Ne::Maximum)
+            .try_into()
+            .unwrap();
+        assert_eq!(vec![0x00, 0xA4, 0x04, 0x00, 0x01, 0x01, 0x00], bytes);
+    }
 }
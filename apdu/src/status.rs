@@ -0,0 +1,428 @@
+//! Interpretation of status words in context of the command that was transmitted.
+//!
+//! The same status word may mean different things depending on the command.
+//! For example, `6A 82` after `SELECT FILE` means the file or application was not found:
+//! ```rust
+//! let command = apdu::command::select_file(0x04, 0x00, &[0xA0, 0x00, 0x00, 0x03, 0x08]);
+//! let response = apdu::Response::from(&[0x6A, 0x82][..]);
+//! let explanation = apdu::status::interpret(&command.into(), response);
+//!
+//! assert_eq!(
+//!     "The file or application to select is not found",
+//!     explanation.to_string(),
+//! );
+//! ```
+
+use crate::command::{
+    INS_GET_DATA_ODD, INS_MANAGE_CHANNEL, INS_READ_BINARY, INS_READ_BINARY_ODD, INS_SELECT_FILE,
+    INS_UPDATE_BINARY_ODD, INS_VERIFY,
+};
+use crate::error::Error;
+
+/// Instruction byte of `UPDATE BINARY` command, in even
+const INS_UPDATE_BINARY: u8 = 0xD6;
+
+/// Instruction byte of `GET DATA` command, in even
+const INS_GET_DATA: u8 = 0xCA;
+
+/// Instruction bytes of `PUT DATA` command, in even and odd
+const INS_PUT_DATA: u8 = 0xDA;
+const INS_PUT_DATA_ODD: u8 = 0xDB;
+
+/// An explanation of the response, interpreted in context of the command
+#[derive(Debug, thiserror::Error)]
+pub enum Explanation {
+    #[error(transparent)]
+    SelectFile(SelectFileStatus),
+
+    #[error(transparent)]
+    ReadBinary(ReadBinaryStatus),
+
+    #[error(transparent)]
+    Verify(VerifyStatus),
+
+    #[error(transparent)]
+    UpdateBinary(UpdateBinaryStatus),
+
+    #[error(transparent)]
+    GetData(GetDataStatus),
+
+    #[error(transparent)]
+    PutData(PutDataStatus),
+
+    #[error(transparent)]
+    ManageChannel(ManageChannelStatus),
+
+    /// The command does not have specific interpretation, so explained in general.
+    #[error(transparent)]
+    General(GeneralStatus),
+}
+
+impl Explanation {
+    /// Interprets the response to a command with the instruction (INS).
+    pub fn new(ins: u8, response: crate::Response) -> Self {
+        match ins {
+            INS_SELECT_FILE => Self::SelectFile(response.into()),
+            INS_READ_BINARY | INS_READ_BINARY_ODD => Self::ReadBinary(response.into()),
+            INS_VERIFY => Self::Verify(response.into()),
+            INS_UPDATE_BINARY | INS_UPDATE_BINARY_ODD => Self::UpdateBinary(response.into()),
+            INS_GET_DATA | INS_GET_DATA_ODD => Self::GetData(response.into()),
+            INS_PUT_DATA | INS_PUT_DATA_ODD => Self::PutData(response.into()),
+            INS_MANAGE_CHANNEL => Self::ManageChannel(response.into()),
+            _ => Self::General(response.into()),
+        }
+    }
+}
+
+/// Interprets the response in context of the command that was transmitted.
+pub fn interpret(command: &crate::Command, response: crate::Response) -> Explanation {
+    Explanation::new(command.ins, response)
+}
+
+/// Status of `SELECT FILE` command
+#[derive(Debug, apdu_derive::Response, thiserror::Error)]
+pub enum SelectFileStatus {
+    #[apdu(0x90, 0x00)]
+    #[error("The file or application is selected")]
+    Selected,
+
+    #[apdu(0x62, 0x83)]
+    #[error("The selected file or application is deactivated")]
+    Deactivated,
+
+    #[apdu(0x62, 0x84)]
+    #[error("The file control information is not formatted according to ISO/IEC 7816-4")]
+    FciNotFormatted,
+
+    #[apdu(0x6A, 0x81)]
+    #[error("Selecting by the method is not supported")]
+    FunctionNotSupported,
+
+    #[apdu(0x6A, 0x82)]
+    #[error("The file or application to select is not found")]
+    NotFound,
+
+    #[apdu(0x6A, 0x86)]
+    #[error("Incorrect parameters P1-P2 for selection")]
+    IncorrectParameters,
+
+    #[apdu(0x6A, 0x87)]
+    #[error("Lc is inconsistent with P1-P2")]
+    LcInconsistent,
+
+    #[apdu(_, _)]
+    #[error(transparent)]
+    Other(#[response] Error),
+}
+
+/// Status of `READ BINARY` command
+#[derive(Debug, apdu_derive::Response, thiserror::Error)]
+pub enum ReadBinaryStatus {
+    #[apdu(0x90, 0x00)]
+    #[error("The data is read")]
+    Read,
+
+    #[apdu(0x62, 0x81)]
+    #[error("Part of the read data may be corrupted")]
+    DataCorrupted,
+
+    #[apdu(0x62, 0x82)]
+    #[error("End of file reached before reading Le bytes")]
+    EndOfFile,
+
+    #[apdu(0x67, 0x00)]
+    #[error("Wrong length: Le is not acceptable")]
+    WrongLength,
+
+    #[apdu(0x69, 0x81)]
+    #[error("The current file is not a transparent EF")]
+    IncompatibleFileStructure,
+
+    #[apdu(0x69, 0x82)]
+    #[error("Security status is not satisfied to read the file")]
+    SecurityStatusNotSatisfied,
+
+    #[apdu(0x69, 0x86)]
+    #[error("No EF is selected to read")]
+    NoCurrentEf,
+
+    #[apdu(0x6A, 0x81)]
+    #[error("Reading by the method is not supported")]
+    FunctionNotSupported,
+
+    #[apdu(0x6A, 0x82)]
+    #[error("The file referenced by the short EF identifier is not found")]
+    FileNotFound,
+
+    #[apdu(0x6B, 0x00)]
+    #[error("The offset is outside of the file")]
+    OffsetOutOfRange,
+
+    #[apdu(0x6C, _)]
+    #[error("Wrong Le: {0} bytes are available")]
+    WrongLe(#[sw2] u8),
+
+    #[apdu(_, _)]
+    #[error(transparent)]
+    Other(#[response] Error),
+}
+
+/// Status of `VERIFY` command
+#[derive(Debug, apdu_derive::Response, thiserror::Error)]
+pub enum VerifyStatus {
+    #[apdu(0x90, 0x00)]
+    #[error("Verification succeeded, or no verification is required")]
+    Verified,
+
+    #[apdu(0x63, 0xC0..=0xCF)]
+    #[error("Verification failed: {0} tries left")]
+    Failed(
+        #[sw2]
+        #[mask(0x0F)]
+        u8,
+    ),
+
+    #[apdu(0x69, 0x83)]
+    #[error("The reference data is blocked")]
+    Blocked,
+
+    #[apdu(0x69, 0x84)]
+    #[error("The reference data is not usable")]
+    NotUsable,
+
+    #[apdu(0x6A, 0x86)]
+    #[error("Incorrect parameters P1-P2")]
+    IncorrectParameters,
+
+    #[apdu(0x6A, 0x88)]
+    #[error("The reference data is not found")]
+    ReferenceDataNotFound,
+
+    #[apdu(_, _)]
+    #[error(transparent)]
+    Other(#[response] Error),
+}
+
+/// Status of `UPDATE BINARY` command
+#[derive(Debug, apdu_derive::Response, thiserror::Error)]
+pub enum UpdateBinaryStatus {
+    #[apdu(0x90, 0x00)]
+    #[error("The data is written")]
+    Updated,
+
+    #[apdu(0x65, 0x81)]
+    #[error("Memory failure while writing the data")]
+    MemoryFailure,
+
+    #[apdu(0x67, 0x00)]
+    #[error("Wrong length: Lc is not acceptable")]
+    WrongLength,
+
+    #[apdu(0x69, 0x81)]
+    #[error("The current file is not a transparent EF")]
+    IncompatibleFileStructure,
+
+    #[apdu(0x69, 0x82)]
+    #[error("Security status is not satisfied to update the file")]
+    SecurityStatusNotSatisfied,
+
+    #[apdu(0x69, 0x86)]
+    #[error("No EF is selected to update")]
+    NoCurrentEf,
+
+    #[apdu(0x6A, 0x81)]
+    #[error("Updating by the method is not supported")]
+    FunctionNotSupported,
+
+    #[apdu(0x6A, 0x82)]
+    #[error("The file referenced by the short EF identifier is not found")]
+    FileNotFound,
+
+    #[apdu(0x6A, 0x84)]
+    #[error("Not enough memory space in the file")]
+    NotEnoughMemory,
+
+    #[apdu(0x6B, 0x00)]
+    #[error("The offset is outside of the file")]
+    OffsetOutOfRange,
+
+    #[apdu(_, _)]
+    #[error(transparent)]
+    Other(#[response] Error),
+}
+
+/// Status of `GET DATA` command
+#[derive(Debug, apdu_derive::Response, thiserror::Error)]
+pub enum GetDataStatus {
+    #[apdu(0x90, 0x00)]
+    #[error("The data is retrieved")]
+    Retrieved,
+
+    #[apdu(0x62, 0x81)]
+    #[error("Part of the retrieved data may be corrupted")]
+    DataCorrupted,
+
+    #[apdu(0x69, 0x82)]
+    #[error("Security status is not satisfied to retrieve the data")]
+    SecurityStatusNotSatisfied,
+
+    #[apdu(0x6A, 0x81)]
+    #[error("Retrieving the data is not supported")]
+    FunctionNotSupported,
+
+    #[apdu(0x6A, 0x82)]
+    #[error("The file referenced is not found")]
+    FileNotFound,
+
+    #[apdu(0x6A, 0x88)]
+    #[error("The data object referenced is not found")]
+    DataNotFound,
+
+    #[apdu(0x6C, _)]
+    #[error("Wrong Le: {0} bytes are available")]
+    WrongLe(#[sw2] u8),
+
+    #[apdu(_, _)]
+    #[error(transparent)]
+    Other(#[response] Error),
+}
+
+/// Status of `PUT DATA` command
+#[derive(Debug, apdu_derive::Response, thiserror::Error)]
+pub enum PutDataStatus {
+    #[apdu(0x90, 0x00)]
+    #[error("The data is stored")]
+    Stored,
+
+    #[apdu(0x65, 0x81)]
+    #[error("Memory failure while storing the data")]
+    MemoryFailure,
+
+    #[apdu(0x69, 0x82)]
+    #[error("Security status is not satisfied to store the data")]
+    SecurityStatusNotSatisfied,
+
+    #[apdu(0x6A, 0x80)]
+    #[error("Incorrect parameters in the data field")]
+    IncorrectData,
+
+    #[apdu(0x6A, 0x84)]
+    #[error("Not enough memory space to store the data")]
+    NotEnoughMemory,
+
+    #[apdu(0x6A, 0x88)]
+    #[error("The data object referenced is not found")]
+    DataNotFound,
+
+    #[apdu(_, _)]
+    #[error(transparent)]
+    Other(#[response] Error),
+}
+
+/// Status of `MANAGE CHANNEL` command
+#[derive(Debug, apdu_derive::Response, thiserror::Error)]
+pub enum ManageChannelStatus {
+    #[apdu(0x90, 0x00)]
+    #[error("The channel is opened or closed")]
+    Managed,
+
+    #[apdu(0x68, 0x81)]
+    #[error("Logical channels are not supported")]
+    ChannelNotSupported,
+
+    #[apdu(0x6A, 0x81)]
+    #[error("No more channels can be opened")]
+    FunctionNotSupported,
+
+    #[apdu(0x6A, 0x86)]
+    #[error("Incorrect parameters P1-P2")]
+    IncorrectParameters,
+
+    #[apdu(_, _)]
+    #[error(transparent)]
+    Other(#[response] Error),
+}
+
+/// Status of commands without specific interpretation
+#[derive(Debug, apdu_derive::Response, thiserror::Error)]
+pub enum GeneralStatus {
+    #[apdu(0x90, 0x00)]
+    #[error("Command successfully executed")]
+    Ok,
+
+    #[apdu(_, _)]
+    #[error(transparent)]
+    Other(#[response] Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Instruction byte of `READ RECORD` command, which has no specific interpretation
+    const INS_READ_RECORD: u8 = 0xB2;
+
+    fn explain(ins: u8, bytes: &[u8]) -> Explanation {
+        Explanation::new(ins, crate::Response::from(bytes))
+    }
+
+    #[test]
+    fn same_status_word_per_command() {
+        assert!(matches!(
+            explain(INS_SELECT_FILE, &[0x6A, 0x82]),
+            Explanation::SelectFile(SelectFileStatus::NotFound),
+        ));
+        assert!(matches!(
+            explain(INS_READ_BINARY, &[0x6A, 0x82]),
+            Explanation::ReadBinary(ReadBinaryStatus::FileNotFound),
+        ));
+        assert!(matches!(
+            explain(INS_READ_RECORD, &[0x6A, 0x82]),
+            Explanation::General(GeneralStatus::Other(Error::FileNotFound)),
+        ));
+    }
+
+    #[test]
+    fn commands_in_command_module() {
+        assert!(matches!(
+            explain(INS_UPDATE_BINARY_ODD, &[0x6A, 0x84]),
+            Explanation::UpdateBinary(UpdateBinaryStatus::NotEnoughMemory),
+        ));
+        assert!(matches!(
+            explain(INS_UPDATE_BINARY, &[0x6B, 0x00]),
+            Explanation::UpdateBinary(UpdateBinaryStatus::OffsetOutOfRange),
+        ));
+        assert!(matches!(
+            explain(INS_GET_DATA, &[0x6A, 0x88]),
+            Explanation::GetData(GetDataStatus::DataNotFound),
+        ));
+        assert!(matches!(
+            explain(INS_GET_DATA_ODD, &[0x6A, 0x88]),
+            Explanation::GetData(GetDataStatus::DataNotFound),
+        ));
+        assert!(matches!(
+            explain(INS_PUT_DATA_ODD, &[0x6A, 0x80]),
+            Explanation::PutData(PutDataStatus::IncorrectData),
+        ));
+        assert!(matches!(
+            explain(INS_MANAGE_CHANNEL, &[0x68, 0x81]),
+            Explanation::ManageChannel(ManageChannelStatus::ChannelNotSupported),
+        ));
+    }
+
+    #[test]
+    fn fallback_to_general_error() {
+        assert!(matches!(
+            explain(INS_VERIFY, &[0x63, 0xC1]),
+            Explanation::Verify(VerifyStatus::Failed(1)),
+        ));
+        assert!(matches!(
+            explain(INS_VERIFY, &[0x69, 0x82]),
+            Explanation::Verify(VerifyStatus::Other(Error::SecurityConditionNotSatisfied)),
+        ));
+        assert_eq!(
+            "Wrong Le: 16 bytes are available",
+            explain(INS_READ_BINARY, &[0x6C, 0x10]).to_string(),
+        );
+    }
+}